@@ -27,7 +27,7 @@ mod employee_contract_events {
     pub fn emit_employee_paid(env: &Env, address: Address, amount: u128) {
         let employee_paid: EmployeePaid = EmployeePaid {
             employee: address,
-            amount: amount,
+            amount,
         };
         env.events().publish(("employee_paid",), employee_paid);
     }
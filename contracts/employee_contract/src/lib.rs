@@ -1,6 +1,8 @@
 #![no_std]
-use sep_0041::Sep0041Client;
-use soroban_sdk::{contract, contracterror, contractimpl, contracttype, Address, Env, String};
+use sep_0041::Sep0041Error;
+use soroban_sdk::{
+    contract, contracterror, contractimpl, contracttype, token::TokenClient, Address, Env, String,
+};
 
 use crate::events::{emit_employee_paid, emit_employee_suspended, emit_remove_employee};
 
@@ -13,12 +15,22 @@ pub struct Employee {
     pub rank: Rank,
 }
 
-#[contracttype]
-#[derive(Debug, PartialEq)]
-pub enum Rank {
-    Level_1,
-    Level_2,
-    Level_3,
+pub use rank::Rank;
+
+// the variant names are part of the stored `Employee` encoding, renaming them would
+// break records that are already on chain. The allow sits on a module of its own as
+// `contracttype` repeats the names in items it generates next to the enum
+#[allow(non_camel_case_types)]
+mod rank {
+    use soroban_sdk::contracttype;
+
+    #[contracttype]
+    #[derive(Debug, PartialEq)]
+    pub enum Rank {
+        Level_1,
+        Level_2,
+        Level_3,
+    }
 }
 
 #[contracttype]
@@ -33,9 +45,12 @@ pub enum DataKey {
 }
 
 #[contracterror]
-#[derive(Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum EmployeeContractError {
     NotAnEmployee = 1,
+    InsufficientAllowance = 2,
+    InsufficientBalance = 3,
+    PaymentFailed = 4,
 }
 
 #[contract]
@@ -124,7 +139,7 @@ impl EmployeeContract {
             .storage()
             .instance()
             .get(&DataKey::Exist(employee_address.clone()))
-            .unwrap_or_else(|| false);
+            .unwrap_or(false);
         if !is_employee {
             return Err(EmployeeContractError::NotAnEmployee);
         }
@@ -154,7 +169,11 @@ impl EmployeeContract {
         Ok(())
     }
 
-    pub fn pay_employee(env: &Env, admin: Address, employee_address: Address) {
+    pub fn pay_employee(
+        env: &Env,
+        admin: Address,
+        employee_address: Address,
+    ) -> Result<(), EmployeeContractError> {
         admin.require_auth();
 
         //get the pay of the employee
@@ -167,18 +186,36 @@ impl EmployeeContract {
 
         let token_address: Address = env.storage().instance().get(&DataKey::Contract).expect("");
 
-        let sep_0041_instance = Sep0041Client::new(env, &token_address);
+        let sep_0041_instance = TokenClient::new(env, &token_address);
 
         let contract_address: Address = env.current_contract_address();
 
-        sep_0041_instance.transfer_from(
+        let payment = sep_0041_instance.try_transfer_from(
             &contract_address,
             &admin,
             &employee_details.address,
             &(employee_pay as i128),
         );
 
+        match payment {
+            Ok(_) => {}
+            Err(Ok(error)) => {
+                return Err(match Sep0041Error::try_from(error) {
+                    Ok(Sep0041Error::InsufficientAllowance)
+                    | Ok(Sep0041Error::AllowanceExpired) => {
+                        EmployeeContractError::InsufficientAllowance
+                    }
+                    Ok(Sep0041Error::InsufficientBalance) => {
+                        EmployeeContractError::InsufficientBalance
+                    }
+                    _ => EmployeeContractError::PaymentFailed,
+                })
+            }
+            Err(Err(_)) => return Err(EmployeeContractError::PaymentFailed),
+        }
+
         emit_employee_paid(env, employee_address, employee_details.pay);
+        Ok(())
     }
 
     pub fn is_employee_suspended(env: &Env, address: Address) -> Option<bool> {
@@ -1,8 +1,9 @@
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod test {
-    use crate::{EmployeeContract, EmployeeContractClient};
-    use sep_0041::contract_sep_41::Sep0041;
-    use sep_0041::Sep0041Client;
+    use crate::{EmployeeContract, EmployeeContractClient, EmployeeContractError};
+    use sep_0041::contract_sep_41::{Sep0041, Sep0041Client};
+    use sep_0041::UNLIMITED_ALLOWANCE;
     use soroban_sdk::{log, testutils::Address as _, Address, Env, String};
 
     fn generate_addresses(env: &Env) -> (Address, Address, Address) {
@@ -29,8 +30,12 @@ mod test {
             Sep0041,
             (
                 admin.clone(),
+                18_u32,
                 String::from_str(&env, "loaded"),
                 String::from_str(&env, "lsd"),
+                false,
+                None::<i128>,
+                None::<Address>,
             ),
         );
 
@@ -82,7 +87,6 @@ mod test {
     }
 
     #[test]
-    #[should_panic]
     fn test_pay_employee_no_allowance() {
         // let (env, employee_client, _, sec_admin,_ _) = setup();
         let (env, employee_client, _, sec_admin, _, _) = setup();
@@ -92,7 +96,27 @@ mod test {
         // add an employee
         employee_client.add_employee(&sec_admin, &employee_name, &a, &2000);
 
-        employee_client.pay_employee(&sec_admin, &a);
+        assert_eq!(
+            employee_client.try_pay_employee(&sec_admin, &a),
+            Err(Ok(EmployeeContractError::InsufficientAllowance))
+        );
+    }
+
+    #[test]
+    fn test_pay_employee_insufficient_balance() {
+        let (env, employee_client, _, sec_admin, sep41_client, c) = setup();
+        let (a, _, _) = generate_addresses(&env);
+        let employee_name = String::from_str(&env, "dele");
+
+        employee_client.add_employee(&sec_admin, &employee_name, &a, &2000);
+        sep41_client.mint(&sec_admin, &1_000);
+        sep41_client.approve(&sec_admin, &c, &2000, &3);
+
+        assert_eq!(
+            employee_client.try_pay_employee(&sec_admin, &a),
+            Err(Ok(EmployeeContractError::InsufficientBalance))
+        );
+        assert_eq!(sep41_client.balance(&sec_admin), 1_000);
     }
 
     #[test]
@@ -151,4 +175,34 @@ mod test {
 
         assert!(result.unwrap());
     }
+
+    #[test]
+    fn test_pay_employee_with_unlimited_allowance() {
+        let (env, employee_client, _, sec_admin, sep41_client, c) = setup();
+        let (a, b, _) = generate_addresses(&env);
+        let employee_name = String::from_str(&env, "dele");
+
+        employee_client.add_employee(&sec_admin, &employee_name, &a, &2000);
+        employee_client.add_employee(&sec_admin, &employee_name, &b, &1500);
+        sep41_client.mint(&sec_admin, &20_000);
+        // approved once for every pay cycle to come
+        sep41_client.approve(&sec_admin, &c, &UNLIMITED_ALLOWANCE, &1_000);
+
+        for _ in 0..3 {
+            employee_client.pay_employee(&sec_admin, &a);
+            employee_client.pay_employee(&sec_admin, &b);
+        }
+
+        assert_eq!(sep41_client.balance(&a), 6_000);
+        assert_eq!(sep41_client.balance(&b), 4_500);
+        assert_eq!(sep41_client.balance(&sec_admin), 9_500);
+        assert_eq!(sep41_client.allowance(&sec_admin, &c), UNLIMITED_ALLOWANCE);
+
+        // the balance still bounds the payments
+        sep41_client.burn(&sec_admin, &9_000);
+        assert_eq!(
+            employee_client.try_pay_employee(&sec_admin, &a),
+            Err(Ok(EmployeeContractError::InsufficientBalance))
+        );
+    }
 }
@@ -1,226 +1,1238 @@
 use crate::errors::Sep0041Error;
-use crate::i_sep_41::ISep0041;
-use crate::storage::{AllowanaceDetails, DataKey, SECONDS_IN_TIME};
-use soroban_sdk::{contract, contractimpl, log, Address, Env, String};
+use crate::events::{
+    emit_add_minter, emit_admin_proposed, emit_admin_renounced, emit_approve, emit_burn,
+    emit_clawback, emit_delegate_changed, emit_delegate_votes_changed, emit_lock, emit_migrate,
+    emit_mint, emit_paused, emit_rebase, emit_release, emit_remove_minter, emit_set_admin,
+    emit_set_authorized, emit_set_fee_exempt, emit_set_max_supply, emit_set_metadata,
+    emit_set_pauser, emit_set_permit_enabled, emit_set_rebase_oracle, emit_set_transfer_fee,
+    emit_transfer, emit_transfer_fee, emit_unpaused, emit_upgrade,
+};
+use crate::i_sep_41::{
+    AllowancesPage, ISep0041Admin, Sep0041ReceiverClient, REBASE_INDEX_ONE, SHARES_PER_TOKEN,
+    UNLIMITED_ALLOWANCE,
+};
+use crate::storage::{
+    AllowanaceDetails, Checkpoint, Checkpoints, DataKey, LegacyAllowanceDetails, LegacyDataKey,
+    Lock, MinterDetails, PendingAdminDetails, PermitPayload, TokenMetadata, BALANCE_BUMP_AMOUNT,
+    BALANCE_LIFETIME_THRESHOLD, BPS_DENOMINATOR, CHECKPOINT_PAGE_SIZE, INSTANCE_BUMP_AMOUNT,
+    INSTANCE_LIFETIME_THRESHOLD, LEDGER_SECONDS, MAX_BATCH_SIZE, MAX_DECIMALS, MAX_FEE_BPS,
+    MAX_LOCKS, MAX_NAME_LENGTH, MAX_SPENDERS, MAX_SYMBOL_LENGTH, METADATA_KEY, SCHEMA_VERSION,
+};
+use crate::strkey::account_address;
+use soroban_sdk::{
+    contract, contractimpl, log, panic_with_error,
+    token::{TokenClient, TokenInterface},
+    xdr::ToXdr,
+    Address, Bytes, BytesN, Env, Map, String, Symbol, Vec,
+};
 
 #[contract]
 pub struct Sep0041;
 
 #[contractimpl]
 impl Sep0041 {
-    pub fn __constructor(env: &Env, admin: Address, name: String, symbol: String) {
+    #[allow(clippy::too_many_arguments)]
+    pub fn __constructor(
+        env: &Env,
+        admin: Address,
+        decimal: u32,
+        name: String,
+        symbol: String,
+        auth_required: bool,
+        max_supply: Option<i128>,
+        underlying: Option<Address>,
+    ) {
         env.storage().instance().set(&DataKey::Admin, &admin);
-        env.storage().instance().set(&DataKey::Name, &name);
-        env.storage().instance().set(&DataKey::Symbol, &symbol);
-        env.storage().instance().set(&DataKey::Decimal, &18_u32);
+        // a wrapper is 1:1 with its underlying token, so amounts must mean the same thing
+        if let Some(underlying) = underlying {
+            if TokenClient::new(env, &underlying).decimals() != decimal {
+                panic_with_error!(env, Sep0041Error::InvalidDecimals);
+            }
+            env.storage()
+                .instance()
+                .set(&DataKey::Underlying, &underlying);
+        }
+        Self::_update_metadata(
+            env,
+            &TokenMetadata {
+                decimal,
+                name,
+                symbol,
+            },
+        );
+        // when set, accounts start out deauthorized until the admin authorizes them
+        env.storage()
+            .instance()
+            .set(&DataKey::AuthRequired, &auth_required);
+        // no cap means the supply is only bounded by i128
+        if let Some(max_supply) = max_supply {
+            if max_supply <= 0 {
+                panic_with_error!(env, Sep0041Error::InvalidMaxSupply);
+            }
+            env.storage()
+                .instance()
+                .set(&DataKey::MaxSupply, &max_supply);
+        }
+        env.storage()
+            .instance()
+            .set(&DataKey::SchemaVersion, &SCHEMA_VERSION);
+        Self::_extend_instance_ttl(env);
     }
 
+    // rounded on its own like every balance, see `rebase` for how far the two can drift apart
     pub fn total_supply(env: &Env) -> i128 {
+        Self::_extend_instance_ttl(env);
         Self::_total_supply(env)
     }
-}
 
-#[contractimpl]
-impl ISep0041 for Sep0041 {
-    // fn init() -> Result<>
-    fn balance(env: &Env, id: Address) -> i128 {
-        // get the balance from the storage
-        Self::_balance(env, &id)
+    // the raw shares behind `balance`, untouched by rebases
+    pub fn shares_of(env: &Env, id: Address) -> i128 {
+        Self::_extend_instance_ttl(env);
+        Self::_shares(env, &id)
+    }
+
+    pub fn total_shares(env: &Env) -> i128 {
+        Self::_extend_instance_ttl(env);
+        Self::_total_shares(env)
+    }
+
+    pub fn max_supply(env: &Env) -> Option<i128> {
+        Self::_extend_instance_ttl(env);
+        Self::_max_supply(env)
+    }
+
+    // writes the same allowance as `approve`, authorized by an ed25519 signature of the
+    // owner over the sha256 of the xdr encoded `PermitPayload` instead of by the owner
+    // account itself. The signature check traps on failure.
+    //
+    // The signature is checked against the account's master key only, its signer weights
+    // and thresholds are not consulted. Permits are therefore off until the account turns
+    // them on through its own signers with `set_permit_enabled`
+    pub fn permit(
+        env: &Env,
+        owner_pubkey: BytesN<32>,
+        spender: Address,
+        amount: i128,
+        expiration_ledger: u32,
+        nonce: u64,
+        signature: BytesN<64>,
+    ) {
+        Self::_extend_instance_ttl(env);
+        Self::_require_not_paused(env);
+
+        let owner: Address = account_address(env, &owner_pubkey);
+        if !Self::_permit_enabled(env, &owner) {
+            panic_with_error!(env, Sep0041Error::PermitNotEnabled);
+        }
+        if nonce != Self::_permit_nonce(env, &owner) {
+            panic_with_error!(env, Sep0041Error::InvalidNonce);
+        }
+
+        let payload: PermitPayload = PermitPayload {
+            domain: Symbol::new(env, "sep41_permit"),
+            network_id: env.ledger().network_id(),
+            contract: env.current_contract_address(),
+            owner: owner_pubkey.clone(),
+            spender: spender.clone(),
+            amount,
+            expiration_ledger,
+            nonce,
+        };
+        let digest = env.crypto().sha256(&payload.to_xdr(env));
+        env.crypto()
+            .ed25519_verify(&owner_pubkey, &digest.into(), &signature);
+
+        Self::_update_permit_nonce(env, &owner, nonce + 1);
+        Self::_approve(env, &owner, &spender, amount, expiration_ledger);
     }
 
-    fn name(env: &Env) -> String {
-        Self::_name(env)
+    // pays every recipient from `from` under a single authorization. The total is debited
+    // up front and each leg emits its own SEP-41 transfer event
+    pub fn batch_transfer(env: &Env, from: Address, recipients: Vec<(Address, i128)>) {
+        Self::_extend_instance_ttl(env);
+        Self::_require_not_paused(env);
+        from.require_auth();
+
+        if recipients.is_empty() {
+            panic_with_error!(env, Sep0041Error::EmptyBatch);
+        }
+        if recipients.len() > MAX_BATCH_SIZE {
+            panic_with_error!(env, Sep0041Error::BatchTooLarge);
+        }
+
+        let mut seen: Map<Address, ()> = Map::new(env);
+        let mut total: i128 = 0;
+        for (to, amount) in recipients.iter() {
+            Self::_check_for_zero_amount(env, amount);
+            if seen.contains_key(to.clone()) {
+                panic_with_error!(env, Sep0041Error::DuplicateRecipient);
+            }
+            seen.set(to, ());
+            total = Self::_checked_add(env, total, amount);
+        }
+
+        Self::_require_authorized(env, &from);
+        let from_balance: i128 = Self::_balance(env, &from);
+        if from_balance < total {
+            panic_with_error!(env, Sep0041Error::InsufficientBalance);
+        }
+        Self::_require_unlocked(env, &from, from_balance, total);
+
+        // every leg rounds on its own, the last ones give way should they add up to more
+        // shares than `from` holds
+        let mut remaining: i128 = Self::_shares(env, &from);
+        let mut leg_shares: Vec<i128> = Vec::new(env);
+        for (_, amount) in recipients.iter() {
+            let shares: i128 = Self::_to_shares(env, amount).min(remaining);
+            remaining -= shares;
+            leg_shares.push_back(shares);
+        }
+        Self::_update_shares(env, &from, remaining);
+
+        for ((to, amount), shares) in recipients.iter().zip(leg_shares.iter()) {
+            Self::_require_authorized(env, &to);
+            Self::_settle(env, &from, &to, amount, shares);
+        }
     }
-    fn decimals(env: &Env) -> u32 {
-        Self::_decimal(env)
+
+    // the balance `id` held at the end of `ledger`, which must already be closed
+    pub fn balance_at(env: &Env, id: Address, ledger: u32) -> i128 {
+        Self::_extend_instance_ttl(env);
+        Self::_require_past_ledger(env, ledger);
+        let shares: i128 = Self::_checkpoint_lookup(
+            env,
+            &DataKey::BalanceCheckpoints(id.clone()),
+            |page| DataKey::BalanceCheckpointPage(id.clone(), page),
+            ledger,
+        )
+        // untouched since checkpoints were introduced, so the shares have not moved
+        .unwrap_or_else(|| Self::_shares(env, &id));
+        Self::_to_amount_at(env, shares, ledger)
     }
-    fn symbol(env: &Env) -> String {
-        Self::_symbol(env)
+
+    // the total supply at the end of `ledger`, which must already be closed
+    pub fn total_supply_at(env: &Env, ledger: u32) -> i128 {
+        Self::_extend_instance_ttl(env);
+        Self::_require_past_ledger(env, ledger);
+        let shares: i128 = Self::_checkpoint_lookup(
+            env,
+            &DataKey::SupplyCheckpoints,
+            DataKey::SupplyCheckpointPage,
+            ledger,
+        )
+        .unwrap_or_else(|| Self::_total_shares(env));
+        Self::_to_amount_at(env, shares, ledger)
     }
 
-    fn mint(env: &Env, to: Address, amount: i128) -> Result<bool, Sep0041Error> {
+    // the live allowances of `owner` in approval order. Expired and revoked ones are pruned
+    // from the index on the way. Approvals made while the index held `MAX_SPENDERS` live
+    // spenders are not listed, the page flags them until they could have expired
+    pub fn allowances_of(env: &Env, owner: Address, start: u32, limit: u32) -> AllowancesPage {
+        Self::_extend_instance_ttl(env);
+        let spenders: Vec<Address> = Self::_spenders(env, &owner);
+        let mut live_spenders: Vec<Address> = Vec::new(env);
+        let mut allowances: Vec<(Address, i128, u32)> = Vec::new(env);
+        for spender in spenders.iter() {
+            let (amount, expiration_ledger) = Self::_allowance(env, &owner, &spender);
+            if amount > 0 {
+                live_spenders.push_back(spender.clone());
+                allowances.push_back((spender, amount, expiration_ledger));
+            }
+        }
+        if live_spenders.len() != spenders.len() {
+            Self::_update_spenders(env, &owner, &live_spenders);
+        }
+
+        let start: u32 = start.min(allowances.len());
+        let end: u32 = start.saturating_add(limit).min(allowances.len());
+        AllowancesPage {
+            allowances: allowances.slice(start..end),
+            unlisted: Self::_has_unlisted(env, &owner),
+        }
+    }
+
+    // atomic alternatives to replacing an allowance with `approve`, the expiration stays
+    // as it is. Both emit the same event as `approve` with the resulting amount
+    pub fn increase_allowance(env: &Env, from: Address, spender: Address, amount: i128) {
+        Self::_extend_instance_ttl(env);
+        Self::_require_not_paused(env);
+        from.require_auth();
+
+        Self::_check_for_zero_amount(env, amount);
+        Self::_adjust_allowance(env, &from, &spender, amount);
+    }
+
+    pub fn decrease_allowance(env: &Env, from: Address, spender: Address, amount: i128) {
+        Self::_extend_instance_ttl(env);
+        Self::_require_not_paused(env);
+        from.require_auth();
+
+        Self::_check_for_zero_amount(env, amount);
+        Self::_adjust_allowance(env, &from, &spender, -amount);
+    }
+
+    // same as approving zero
+    pub fn revoke(env: &Env, from: Address, spender: Address) {
+        Self::_extend_instance_ttl(env);
+        Self::_require_not_paused(env);
+        from.require_auth();
+
+        Self::_approve(env, &from, &spender, 0, Self::_current_ledger(env));
+    }
+
+    // transfers to a contract and notifies it through its `ISep0041Receiver` hook
+    pub fn transfer_and_call(env: &Env, from: Address, to: Address, amount: i128, data: Bytes) {
+        Self::_extend_instance_ttl(env);
+        Self::_require_not_paused(env);
+        from.require_auth();
+
+        Self::_check_for_zero_amount(env, amount);
+        let received: i128 = Self::_transfer(env, &from, &to, amount);
+
+        let receiver: Sep0041ReceiverClient = Sep0041ReceiverClient::new(env, &to);
+        match receiver.try_on_sep41_received(&from, &received, &data) {
+            Ok(Ok(true)) => {}
+            // rejected, failed, or `to` does not implement the hook at all
+            _ => panic_with_error!(env, Sep0041Error::ReceiverRejected),
+        }
+    }
+
+    // keeps `amount` of the balance of `from` from being spent before `unlock_ledger`
+    pub fn lock(env: &Env, from: Address, amount: i128, unlock_ledger: u32) {
+        Self::_extend_instance_ttl(env);
+        Self::_require_not_paused(env);
+        from.require_auth();
+
+        Self::_check_for_zero_amount(env, amount);
+        let balance: i128 = Self::_balance(env, &from);
+        if balance < amount {
+            panic_with_error!(env, Sep0041Error::InsufficientBalance);
+        }
+        Self::_require_unlocked(env, &from, balance, amount);
+        Self::_add_lock(env, &from, amount, unlock_ledger);
+    }
+
+    // drops the expired locks of `id` and returns the amount they held
+    pub fn release(env: &Env, id: Address) -> i128 {
+        Self::_extend_instance_ttl(env);
+        let released: i128 = Self::_release_expired(env, &id);
+        if released > 0 {
+            emit_release(env, id, released);
+        }
+        released
+    }
+
+    // expired locks count as spendable even before they are released
+    pub fn locked_balance(env: &Env, id: Address) -> i128 {
+        Self::_extend_instance_ttl(env);
+        // a clawback can take locked tokens, the balance bounds what is still locked
+        Self::_locked(env, &id).min(Self::_balance(env, &id))
+    }
+
+    pub fn spendable_balance(env: &Env, id: Address) -> i128 {
+        Self::_extend_instance_ttl(env);
+        let balance: i128 = Self::_balance(env, &id);
+        balance - Self::_locked(env, &id).min(balance)
+    }
+
+    // pulls `amount` of the underlying token from `from` and mints the same amount of
+    // wrapped tokens to it
+    pub fn deposit(env: &Env, from: Address, amount: i128) {
+        Self::_extend_instance_ttl(env);
+        Self::_require_not_paused(env);
+        from.require_auth();
+
+        Self::_check_for_zero_amount(env, amount);
+        let underlying: TokenClient = Self::_underlying_client(env);
+        underlying.transfer(&from, &env.current_contract_address(), &amount);
+
+        Self::_mint(env, &from, amount);
+        emit_mint(env, env.current_contract_address(), from, amount);
+        Self::_require_backed(env, &underlying);
+    }
+
+    // burns `amount` of wrapped tokens from `from` and sends it the same amount of the
+    // underlying token
+    pub fn withdraw(env: &Env, from: Address, amount: i128) {
+        Self::_extend_instance_ttl(env);
+        Self::_require_not_paused(env);
+        from.require_auth();
+
+        Self::_check_for_zero_amount(env, amount);
+        let underlying: TokenClient = Self::_underlying_client(env);
+        Self::_burn(env, &from, amount);
+        underlying.transfer(&env.current_contract_address(), &from, &amount);
+        Self::_require_backed(env, &underlying);
+    }
+
+    pub fn underlying(env: &Env) -> Option<Address> {
+        Self::_extend_instance_ttl(env);
+        Self::_underlying(env)
+    }
+
+    // the underlying tokens held by the contract, 0 when it does not wrap a token
+    pub fn reserves(env: &Env) -> i128 {
+        Self::_extend_instance_ttl(env);
+        match Self::_underlying(env) {
+            Some(underlying) => {
+                TokenClient::new(env, &underlying).balance(&env.current_contract_address())
+            }
+            None => 0,
+        }
+    }
+
+    // moves the voting power of `from` to `delegatee`, balances only count as votes once
+    // delegated, to the holder itself or to someone else
+    pub fn delegate(env: &Env, from: Address, delegatee: Address) {
+        Self::_extend_instance_ttl(env);
+        from.require_auth();
+
+        let previous: Option<Address> = Self::_delegate(env, &from);
+        env.storage()
+            .persistent()
+            .set(&DataKey::Delegate(from.clone()), &delegatee);
+        env.storage().persistent().extend_ttl(
+            &DataKey::Delegate(from.clone()),
+            BALANCE_LIFETIME_THRESHOLD,
+            BALANCE_BUMP_AMOUNT,
+        );
+        emit_delegate_changed(env, from.clone(), previous.clone(), delegatee.clone());
+
+        if previous.as_ref() != Some(&delegatee) {
+            let shares: i128 = Self::_shares(env, &from);
+            if let Some(previous) = previous {
+                Self::_move_votes(env, &previous, -shares);
+            }
+            Self::_move_votes(env, &delegatee, shares);
+        }
+    }
+
+    pub fn delegates(env: &Env, id: Address) -> Option<Address> {
+        Self::_extend_instance_ttl(env);
+        Self::_delegate(env, &id)
+    }
+
+    pub fn get_votes(env: &Env, id: Address) -> i128 {
+        Self::_extend_instance_ttl(env);
+        Self::_to_amount(env, Self::_votes(env, &id))
+    }
+
+    // the votes of `id` at the end of `ledger`, which must already be closed
+    pub fn get_past_votes(env: &Env, id: Address, ledger: u32) -> i128 {
+        Self::_extend_instance_ttl(env);
+        Self::_require_past_ledger(env, ledger);
+        let shares: i128 = Self::_checkpoint_lookup(
+            env,
+            &DataKey::VotesCheckpoints(id.clone()),
+            |page| DataKey::VotesCheckpointPage(id.clone(), page),
+            ledger,
+        )
+        .unwrap_or(0);
+        Self::_to_amount_at(env, shares, ledger)
+    }
+
+    // the nonce the next permit signed by `owner` must carry
+    pub fn permit_nonce(env: &Env, owner: Address) -> u64 {
+        Self::_extend_instance_ttl(env);
+        Self::_permit_nonce(env, &owner)
+    }
+
+    // lets `owner` turn permits for its account on or back off, authorized through the
+    // account's own signers. Either way the nonce advances, so permits already signed can no
+    // longer be used
+    pub fn set_permit_enabled(env: &Env, owner: Address, enabled: bool) {
+        Self::_extend_instance_ttl(env);
+        owner.require_auth();
+
+        let nonce: u64 = Self::_permit_nonce(env, &owner);
+        Self::_update_permit_nonce(env, &owner, nonce + 1);
+        let enabled_key: DataKey = DataKey::PermitEnabled(owner.clone());
+        if enabled {
+            env.storage().persistent().set(&enabled_key, &true);
+            env.storage().persistent().extend_ttl(
+                &enabled_key,
+                BALANCE_LIFETIME_THRESHOLD,
+                BALANCE_BUMP_AMOUNT,
+            );
+        } else {
+            env.storage().persistent().remove(&enabled_key);
+        }
+
+        emit_set_permit_enabled(env, owner, enabled);
+    }
+
+    pub fn permit_enabled(env: &Env, owner: Address) -> bool {
+        Self::_extend_instance_ttl(env);
+        Self::_permit_enabled(env, &owner)
+    }
+}
+
+#[contractimpl]
+impl ISep0041Admin for Sep0041 {
+    fn mint(env: &Env, to: Address, amount: i128) {
+        Self::_extend_instance_ttl(env);
+        Self::_require_not_paused(env);
         // only admin
         let admin: Address = Self::_admin(env);
         // throw error next
         admin.require_auth();
 
-        Self::_check_for_zero_amount(amount);
-        log!(env, "before balance");
+        Self::_check_for_zero_amount(env, amount);
+        Self::_require_unwrapped(env);
+        Self::_mint(env, &to, amount);
 
-        let to_balance = Self::_balance(&env, &to);
+        emit_mint(env, admin, to, amount);
+    }
 
-        log!(env, "after balance");
+    fn admin(env: &Env) -> Address {
+        Self::_extend_instance_ttl(env);
+        Self::_admin(env)
+    }
 
-        let new_balance: i128 = to_balance + amount;
-        // save the new balance
-        Self::_update_balance(env, &to, new_balance);
-        let total_supply: i128 = Self::_total_supply(env);
-        Self::_update_total_supply(env, total_supply + amount);
-        Ok(true)
+    fn propose_admin(env: &Env, new_admin: Address, live_until_ledger: u32) {
+        Self::_extend_instance_ttl(env);
+        let admin: Address = Self::_admin(env);
+        admin.require_auth();
+
+        if live_until_ledger < Self::_current_ledger(env) {
+            panic_with_error!(env, Sep0041Error::InvalidExpirationLedger);
+        }
+
+        // a new proposal replaces any earlier one
+        env.storage().instance().set(
+            &DataKey::PendingAdmin,
+            &PendingAdminDetails {
+                admin: new_admin.clone(),
+                live_until_ledger,
+            },
+        );
+
+        emit_admin_proposed(env, admin, new_admin, live_until_ledger);
     }
 
-    fn allowance(env: &Env, from: Address, spender: Address) -> i128 {
-        let (amount, _) = Self::_allowance(env, &from, &spender);
-        amount
+    fn accept_admin(env: &Env) {
+        Self::_extend_instance_ttl(env);
+        let pending: PendingAdminDetails = env
+            .storage()
+            .instance()
+            .get(&DataKey::PendingAdmin)
+            .unwrap_or_else(|| panic_with_error!(env, Sep0041Error::NoPendingAdmin));
+        pending.admin.require_auth();
+
+        if pending.live_until_ledger < Self::_current_ledger(env) {
+            panic_with_error!(env, Sep0041Error::PendingAdminExpired);
+        }
+
+        let admin: Address = Self::_admin(env);
+        env.storage()
+            .instance()
+            .set(&DataKey::Admin, &pending.admin);
+        env.storage().instance().remove(&DataKey::PendingAdmin);
+
+        emit_set_admin(env, admin, pending.admin);
     }
 
-    fn approve(env: &Env, from: Address, spender: Address, amount: i128, live_until_ledger: u32) {
-        from.require_auth();
-        // log!(env, "left the auth");
+    fn renounce_admin(env: &Env) {
+        Self::_extend_instance_ttl(env);
+        let admin: Address = Self::_admin(env);
+        admin.require_auth();
 
-        // log!(env, "first line");
-        Self::_check_for_zero_amount(amount);
+        env.storage().instance().remove(&DataKey::Admin);
+        env.storage().instance().remove(&DataKey::PendingAdmin);
 
-        // log!(env, "second line");
-        let deadline_time_stamp: u64 = live_until_ledger as u64 * SECONDS_IN_TIME;
+        emit_admin_renounced(env, admin);
+    }
 
-        // log!(env, "third line");
-        assert!(deadline_time_stamp > Self::_current_time_stame(env));
+    fn set_authorized(env: &Env, id: Address, authorize: bool) {
+        Self::_extend_instance_ttl(env);
+        let admin: Address = Self::_admin(env);
+        admin.require_auth();
 
-        //now create the details and save
-        // let tx_details: AllowanaceDetails = Self::_create_allowance_details(amount, env.ledger().timestamp() + (live_until_ledger as u64 * SECONDS_IN_TIME));
-        //
-        // log!(env, "fifth");
-        // Self::_update_allowance(env, from, spender, tx_details);
-        let deadline_time_stamp: u64 = live_until_ledger as u64 * SECONDS_IN_TIME;
-        //
-        // log!(env, "third line");
-        assert!(deadline_time_stamp > Self::_current_time_stame(env));
-        //
-        // //now create the details and save
-        let tx_details: AllowanaceDetails = Self::_create_allowance_details(
-            amount,
-            env.ledger().timestamp() + (live_until_ledger as u64 * SECONDS_IN_TIME),
+        let authorized_key: DataKey = DataKey::Authorized(id.clone());
+        env.storage().persistent().set(&authorized_key, &authorize);
+        env.storage().persistent().extend_ttl(
+            &authorized_key,
+            BALANCE_LIFETIME_THRESHOLD,
+            BALANCE_BUMP_AMOUNT,
         );
-        //
-        // log!(env, "fifth");
-        Self::_update_allowance(env, from, spender, tx_details);
+
+        emit_set_authorized(env, admin, id, authorize);
     }
 
-    fn transfer(env: &Env, from: Address, to: Address, amount: i128) {
-        from.require_auth();
+    fn authorized(env: &Env, id: Address) -> bool {
+        Self::_extend_instance_ttl(env);
+        Self::_authorized(env, &id)
+    }
+
+    fn clawback(env: &Env, from: Address, amount: i128) {
+        Self::_extend_instance_ttl(env);
+        let admin: Address = Self::_admin(env);
+        admin.require_auth();
+
+        Self::_check_for_zero_amount(env, amount);
+        Self::_require_unwrapped(env);
+        Self::_debit(env, &from, amount);
 
-        Self::_check_for_zero_amount(amount);
-        Self::_transfer(env, &from, &to, amount);
+        emit_clawback(env, admin, from, amount);
     }
 
-    fn burn(env: &Env, from: Address, amount: i128) {
-        from.require_auth();
+    fn set_pauser(env: &Env, pauser: Address) {
+        Self::_extend_instance_ttl(env);
+        let admin: Address = Self::_admin(env);
+        admin.require_auth();
 
-        //get from the from balance
-        let from_balance: i128 = Self::_balance(env, &from);
+        env.storage().instance().set(&DataKey::Pauser, &pauser);
+
+        emit_set_pauser(env, admin, pauser);
+    }
 
-        assert!(from_balance >= amount,);
+    fn pause(env: &Env, caller: Address) {
+        Self::_extend_instance_ttl(env);
+        Self::_require_admin_or_pauser(env, &caller);
 
-        Self::_burn(env, &from, amount, from_balance);
+        env.storage().instance().set(&DataKey::Paused, &true);
+
+        emit_paused(env, caller);
     }
 
-    fn transfer_from(env: &Env, spender: Address, from: Address, to: Address, amount: i128) {
-        spender.require_auth();
-        // check allowance
-        // check allowance deadline
-        let (allowance, deadline) = Self::_allowance(env, &from, &spender);
+    fn unpause(env: &Env, caller: Address) {
+        Self::_extend_instance_ttl(env);
+        Self::_require_admin_or_pauser(env, &caller);
+
+        env.storage().instance().set(&DataKey::Paused, &false);
+
+        emit_unpaused(env, caller);
+    }
+
+    fn paused(env: &Env) -> bool {
+        Self::_extend_instance_ttl(env);
+        Self::_paused(env)
+    }
+
+    fn set_metadata(env: &Env, name: String, symbol: String) {
+        Self::_extend_instance_ttl(env);
+        let admin: Address = Self::_admin(env);
+        admin.require_auth();
+
+        let metadata: TokenMetadata = TokenMetadata {
+            decimal: Self::_metadata(env).decimal,
+            name: name.clone(),
+            symbol: symbol.clone(),
+        };
+        Self::_update_metadata(env, &metadata);
+
+        emit_set_metadata(env, admin, name, symbol);
+    }
+
+    fn add_minter(env: &Env, minter: Address, quota: i128, period_ledgers: u32) {
+        Self::_extend_instance_ttl(env);
+        let admin: Address = Self::_admin(env);
+        admin.require_auth();
+
+        if quota <= 0 || period_ledgers == 0 {
+            panic_with_error!(env, Sep0041Error::InvalidMinterConfig);
+        }
+        let minter_details: MinterDetails = MinterDetails {
+            quota,
+            period_ledgers,
+            period_start: Self::_current_ledger(env),
+            minted_in_period: 0,
+        };
+        Self::_update_minter(env, &minter, &minter_details);
+
+        emit_add_minter(env, admin, minter, quota, period_ledgers);
+    }
+
+    fn remove_minter(env: &Env, minter: Address) {
+        Self::_extend_instance_ttl(env);
+        let admin: Address = Self::_admin(env);
+        admin.require_auth();
+
+        env.storage()
+            .persistent()
+            .remove(&DataKey::Minter(minter.clone()));
+
+        emit_remove_minter(env, admin, minter);
+    }
+
+    fn is_minter(env: &Env, id: Address) -> bool {
+        Self::_extend_instance_ttl(env);
+        Self::_minter(env, &id).is_some()
+    }
+
+    fn minter_mint(env: &Env, minter: Address, to: Address, amount: i128) {
+        Self::_extend_instance_ttl(env);
+        Self::_require_not_paused(env);
+        minter.require_auth();
+
+        Self::_check_for_zero_amount(env, amount);
+        Self::_require_unwrapped(env);
+        let mut minter_details: MinterDetails = Self::_minter(env, &minter)
+            .unwrap_or_else(|| panic_with_error!(env, Sep0041Error::NotMinter));
+
+        // start a new period once the current one has run its course
+        let current_ledger: u32 = Self::_current_ledger(env);
+        let elapsed: u32 = current_ledger - minter_details.period_start;
+        if elapsed >= minter_details.period_ledgers {
+            minter_details.period_start =
+                current_ledger - (elapsed % minter_details.period_ledgers);
+            minter_details.minted_in_period = 0;
+        }
+
+        let minted_in_period: i128 =
+            Self::_checked_add(env, minter_details.minted_in_period, amount);
+        if minted_in_period > minter_details.quota {
+            panic_with_error!(env, Sep0041Error::MinterQuotaExceeded);
+        }
+        minter_details.minted_in_period = minted_in_period;
+        Self::_update_minter(env, &minter, &minter_details);
+
+        Self::_mint(env, &to, amount);
+
+        emit_mint(env, minter, to, amount);
+    }
+
+    fn upgrade(env: &Env, new_wasm_hash: BytesN<32>) {
+        Self::_extend_instance_ttl(env);
+        let admin: Address = Self::_admin(env);
+        admin.require_auth();
+
+        env.deployer()
+            .update_current_contract_wasm(new_wasm_hash.clone());
+
+        emit_upgrade(env, admin, new_wasm_hash);
+    }
+
+    fn schema_version(env: &Env) -> u32 {
+        Self::_extend_instance_ttl(env);
+        Self::_schema_version(env)
+    }
+
+    fn migrate(env: &Env, holders: Vec<Address>) {
+        Self::_extend_instance_ttl(env);
+        let admin: Address = Self::_admin(env);
+        admin.require_auth();
 
-        let current_time_stamp: u64 = env.ledger().timestamp();
+        let from_version: u32 = Self::_schema_version(env);
+        if from_version < 1 {
+            Self::_migrate_legacy_metadata(env);
+        }
+        // balances can be moved in batches, this is a no-op for holders already migrated
+        for holder in holders.iter() {
+            Self::_migrate_legacy_balance(env, &holder);
+        }
+        env.storage()
+            .instance()
+            .set(&DataKey::SchemaVersion, &SCHEMA_VERSION);
+
+        emit_migrate(env, admin, from_version, SCHEMA_VERSION);
+    }
+
+    fn migrate_allowances(env: &Env, allowances: Vec<(Address, Address)>) {
+        Self::_extend_instance_ttl(env);
+        let admin: Address = Self::_admin(env);
+        admin.require_auth();
+
+        // a no-op for pairs already migrated or never approved
+        for (owner, spender) in allowances.iter() {
+            Self::_migrate_legacy_allowance(env, &owner, &spender);
+        }
+    }
+
+    fn set_max_supply(env: &Env, max_supply: i128) {
+        Self::_extend_instance_ttl(env);
+        let admin: Address = Self::_admin(env);
+        admin.require_auth();
+
+        let raises_cap: bool = match Self::_max_supply(env) {
+            Some(current) => max_supply > current,
+            None => false,
+        };
+        if raises_cap || max_supply < Self::_total_supply(env) || max_supply <= 0 {
+            panic_with_error!(env, Sep0041Error::InvalidMaxSupply);
+        }
+        env.storage()
+            .instance()
+            .set(&DataKey::MaxSupply, &max_supply);
+
+        emit_set_max_supply(env, admin, max_supply);
+    }
+
+    fn set_transfer_fee(env: &Env, fee_bps: u32, collector: Address) {
+        Self::_extend_instance_ttl(env);
+        let admin: Address = Self::_admin(env);
+        admin.require_auth();
+
+        if fee_bps > MAX_FEE_BPS {
+            panic_with_error!(env, Sep0041Error::InvalidFee);
+        }
+        Self::_require_authorized(env, &collector);
+        env.storage()
+            .instance()
+            .set(&DataKey::TransferFeeBps, &fee_bps);
+        env.storage()
+            .instance()
+            .set(&DataKey::FeeCollector, &collector);
+
+        emit_set_transfer_fee(env, admin, fee_bps, collector);
+    }
+
+    fn transfer_fee(env: &Env) -> u32 {
+        Self::_extend_instance_ttl(env);
+        Self::_transfer_fee_bps(env)
+    }
+
+    fn fee_collector(env: &Env) -> Option<Address> {
+        Self::_extend_instance_ttl(env);
+        env.storage().instance().get(&DataKey::FeeCollector)
+    }
 
-        assert!(
-            allowance >= amount && deadline >= current_time_stamp,
-            "insufficient allowance or exceed deadline"
+    fn set_fee_exempt(env: &Env, id: Address, exempt: bool) {
+        Self::_extend_instance_ttl(env);
+        let admin: Address = Self::_admin(env);
+        admin.require_auth();
+
+        let exempt_key: DataKey = DataKey::FeeExempt(id.clone());
+        if exempt {
+            env.storage().persistent().set(&exempt_key, &true);
+            env.storage().persistent().extend_ttl(
+                &exempt_key,
+                BALANCE_LIFETIME_THRESHOLD,
+                BALANCE_BUMP_AMOUNT,
+            );
+        } else {
+            env.storage().persistent().remove(&exempt_key);
+        }
+
+        emit_set_fee_exempt(env, admin, id, exempt);
+    }
+
+    fn fee_exempt(env: &Env, id: Address) -> bool {
+        Self::_extend_instance_ttl(env);
+        Self::_fee_exempt(env, &id)
+    }
+
+    fn mint_locked(env: &Env, to: Address, amount: i128, unlock_ledger: u32) {
+        Self::_extend_instance_ttl(env);
+        Self::_require_not_paused(env);
+        let admin: Address = Self::_admin(env);
+        admin.require_auth();
+
+        Self::_check_for_zero_amount(env, amount);
+        Self::_require_unwrapped(env);
+        Self::_mint(env, &to, amount);
+        emit_mint(env, admin, to.clone(), amount);
+        Self::_add_lock(env, &to, amount, unlock_ledger);
+    }
+
+    fn set_rebase_oracle(env: &Env, oracle: Address) {
+        Self::_extend_instance_ttl(env);
+        let admin: Address = Self::_admin(env);
+        admin.require_auth();
+
+        env.storage()
+            .instance()
+            .set(&DataKey::RebaseOracle, &oracle);
+
+        emit_set_rebase_oracle(env, admin, oracle);
+    }
+
+    fn rebase(env: &Env, caller: Address, index: i128) {
+        Self::_extend_instance_ttl(env);
+        Self::_require_not_paused(env);
+        Self::_require_admin_or_oracle(env, &caller);
+
+        if index <= 0 {
+            panic_with_error!(env, Sep0041Error::InvalidRebaseIndex);
+        }
+        // the reserves of a wrapper do not follow the index
+        Self::_require_unwrapped(env);
+        // every balance is at most the total supply, so none of them can overflow either
+        let total_supply: i128 = Self::_mul_div(
+            env,
+            Self::_total_shares(env),
+            index,
+            REBASE_INDEX_ONE * SHARES_PER_TOKEN,
         );
+        if let Some(max_supply) = Self::_max_supply(env) {
+            if total_supply > max_supply {
+                panic_with_error!(env, Sep0041Error::MaxSupplyExceeded);
+            }
+        }
 
-        // transfer
-        Self::_transfer(env, &from, &to, amount);
-        // update allowance
-        let tx_details: AllowanaceDetails =
-            Self::_create_allowance_details(allowance - amount, deadline);
-        Self::_update_allowance(env, from, spender, tx_details);
+        let previous: i128 = Self::_index(env);
+        Self::_write_checkpoint(
+            env,
+            &DataKey::IndexCheckpoints,
+            DataKey::IndexCheckpointPage,
+            previous,
+            index,
+        );
+        env.storage().instance().set(&DataKey::RebaseIndex, &index);
+
+        emit_rebase(env, caller, previous, index);
+    }
+
+    fn rebase_index(env: &Env) -> i128 {
+        Self::_extend_instance_ttl(env);
+        Self::_index(env)
+    }
+}
+
+#[contractimpl]
+impl TokenInterface for Sep0041 {
+    fn balance(env: Env, id: Address) -> i128 {
+        Self::_extend_instance_ttl(&env);
+        // get the balance from the storage
+        Self::_balance(&env, &id)
+    }
+
+    fn name(env: Env) -> String {
+        Self::_extend_instance_ttl(&env);
+        Self::_name(&env)
+    }
+    fn decimals(env: Env) -> u32 {
+        Self::_extend_instance_ttl(&env);
+        Self::_decimal(&env)
+    }
+    fn symbol(env: Env) -> String {
+        Self::_extend_instance_ttl(&env);
+        Self::_symbol(&env)
+    }
+
+    fn allowance(env: Env, from: Address, spender: Address) -> i128 {
+        Self::_extend_instance_ttl(&env);
+        let (amount, _) = Self::_allowance(&env, &from, &spender);
+        amount
+    }
+
+    fn approve(env: Env, from: Address, spender: Address, amount: i128, expiration_ledger: u32) {
+        Self::_extend_instance_ttl(&env);
+        Self::_require_not_paused(&env);
+        from.require_auth();
+
+        Self::_approve(&env, &from, &spender, amount, expiration_ledger);
+    }
+
+    fn transfer(env: Env, from: Address, to: Address, amount: i128) {
+        Self::_extend_instance_ttl(&env);
+        Self::_require_not_paused(&env);
+        from.require_auth();
+
+        Self::_check_for_zero_amount(&env, amount);
+        Self::_transfer(&env, &from, &to, amount);
+    }
+
+    fn burn(env: Env, from: Address, amount: i128) {
+        Self::_extend_instance_ttl(&env);
+        Self::_require_not_paused(&env);
+        from.require_auth();
+
+        Self::_check_for_zero_amount(&env, amount);
+        Self::_burn(&env, &from, amount);
     }
 
-    fn burn_from(env: &Env, spender: Address, from: Address, amount: i128) {
+    fn transfer_from(env: Env, spender: Address, from: Address, to: Address, amount: i128) {
+        Self::_extend_instance_ttl(&env);
+        Self::_require_not_paused(&env);
         spender.require_auth();
-        let (allowance, deadline) = Self::_allowance(env, &from, &spender);
-        let current_time_stamp: u64 = env.ledger().timestamp();
 
-        assert!(
-            allowance >= amount && deadline >= current_time_stamp,
-            "insufficient allowance or exceed deadline"
-        );
+        Self::_check_for_zero_amount(&env, amount);
+        // check and update allowance
+        Self::_spend_allowance(&env, &from, &spender, amount);
+        // transfer
+        Self::_transfer(&env, &from, &to, amount);
+    }
 
-        let from_balance: i128 = Self::_balance(env, &from);
-        Self::_burn(env, &from, amount, from_balance);
+    fn burn_from(env: Env, spender: Address, from: Address, amount: i128) {
+        Self::_extend_instance_ttl(&env);
+        Self::_require_not_paused(&env);
+        spender.require_auth();
 
-        let tx_details: AllowanaceDetails =
-            Self::_create_allowance_details(allowance - amount, deadline);
-        Self::_update_allowance(env, from, spender, tx_details);
+        Self::_check_for_zero_amount(&env, amount);
+        Self::_spend_allowance(&env, &from, &spender, amount);
+        Self::_burn(&env, &from, amount);
     }
 }
 
 impl Sep0041 {
-    fn _check_for_zero_amount(amount: i128) {
-        assert!(amount > 0, "invalid amount");
+    fn _check_for_zero_amount(env: &Env, amount: i128) {
+        if amount <= 0 {
+            panic_with_error!(env, Sep0041Error::InvalidAmount);
+        }
+    }
+    fn _checked_add(env: &Env, a: i128, b: i128) -> i128 {
+        a.checked_add(b)
+            .unwrap_or_else(|| panic_with_error!(env, Sep0041Error::Overflow))
     }
-    fn _current_time_stame(env: &Env) -> u64 {
-        env.ledger().timestamp()
+    fn _checked_sub(env: &Env, a: i128, b: i128) -> i128 {
+        a.checked_sub(b)
+            .unwrap_or_else(|| panic_with_error!(env, Sep0041Error::Overflow))
+    }
+    fn _checked_mul(env: &Env, a: i128, b: i128) -> i128 {
+        a.checked_mul(b)
+            .unwrap_or_else(|| panic_with_error!(env, Sep0041Error::Overflow))
+    }
+    fn _current_ledger(env: &Env) -> u32 {
+        env.ledger().sequence()
     }
 
     fn _name(env: &Env) -> String {
-        env.storage().instance().get(&DataKey::Name).unwrap()
+        Self::_metadata(env).name
     }
     fn _decimal(env: &Env) -> u32 {
-        env.storage().instance().get(&DataKey::Decimal).unwrap()
+        Self::_metadata(env).decimal
     }
     fn _symbol(env: &Env) -> String {
-        env.storage().instance().get(&DataKey::Symbol).unwrap()
+        Self::_metadata(env).symbol
+    }
+
+    fn _metadata(env: &Env) -> TokenMetadata {
+        env.storage()
+            .instance()
+            .get(&METADATA_KEY)
+            .unwrap_or_else(|| panic_with_error!(env, Sep0041Error::NotInitialized))
+    }
+
+    fn _update_metadata(env: &Env, metadata: &TokenMetadata) {
+        if metadata.decimal > MAX_DECIMALS {
+            panic_with_error!(env, Sep0041Error::InvalidDecimals);
+        }
+        // names allow spaces and a little punctuation, symbols are plain alphanumerics
+        if !Self::_is_valid_text(&metadata.name, MAX_NAME_LENGTH, b" -_.") {
+            panic_with_error!(env, Sep0041Error::InvalidName);
+        }
+        if !Self::_is_valid_text(&metadata.symbol, MAX_SYMBOL_LENGTH, b"") {
+            panic_with_error!(env, Sep0041Error::InvalidSymbol);
+        }
+        env.storage().instance().set(&METADATA_KEY, metadata);
+    }
+
+    fn _is_valid_text(text: &String, max_length: u32, extra_chars: &[u8]) -> bool {
+        let length: u32 = text.len();
+        if length == 0 || length > max_length {
+            return false;
+        }
+        let mut buffer = [0_u8; MAX_NAME_LENGTH as usize];
+        let bytes = &mut buffer[..length as usize];
+        text.copy_into_slice(bytes);
+        bytes
+            .iter()
+            .all(|byte| byte.is_ascii_alphanumeric() || extra_chars.contains(byte))
     }
 
     fn _admin(env: &Env) -> Address {
-        env.storage().instance().get(&DataKey::Admin).unwrap()
+        // the admin is only ever missing once it has been renounced
+        env.storage()
+            .instance()
+            .get(&DataKey::Admin)
+            .unwrap_or_else(|| panic_with_error!(env, Sep0041Error::NoAdmin))
     }
 
-    fn _create_allowance_details(amount: i128, deadline: u64) -> AllowanaceDetails {
-        // log!("fourth line");
-        AllowanaceDetails { amount, deadline }
+    fn _create_allowance_details(amount: i128, expiration_ledger: u32) -> AllowanaceDetails {
+        AllowanaceDetails {
+            amount,
+            expiration_ledger,
+        }
     }
 
-    fn _burn(env: &Env, from: &Address, amount: i128, from_balance: i128) {
-        // we update the states, from balance and the total supply
-        let from_new_balance: i128 = from_balance - amount;
+    fn _mint(env: &Env, to: &Address, amount: i128) {
+        Self::_require_authorized(env, to);
+        log!(env, "before balance");
+
+        let to_shares = Self::_shares(env, to);
 
-        // call the update method
-        Self::_update_balance(env, &from, from_new_balance);
+        log!(env, "after balance");
+
+        let shares: i128 = Self::_to_shares(env, amount);
+        if shares == 0 {
+            panic_with_error!(env, Sep0041Error::InvalidAmount);
+        }
+        let new_shares: i128 = Self::_checked_add(env, to_shares, shares);
+        let total_shares: i128 = Self::_checked_add(env, Self::_total_shares(env), shares);
+        if let Some(max_supply) = Self::_max_supply(env) {
+            if Self::_to_amount(env, total_shares) > max_supply {
+                panic_with_error!(env, Sep0041Error::MaxSupplyExceeded);
+            }
+        }
+        // save the new balance
+        Self::_update_shares(env, to, new_shares);
+        Self::_update_total_shares(env, total_shares);
+    }
 
-        // get total_supply
-        let total_supply: i128 = Self::_total_supply(env);
+    fn _burn(env: &Env, from: &Address, amount: i128) {
+        Self::_require_authorized(env, from);
+        let from_balance: i128 = Self::_balance(env, from);
+        Self::_require_unlocked(env, from, from_balance, amount);
+        Self::_debit(env, from, amount);
+
+        emit_burn(env, from.clone(), amount);
+    }
+
+    // removes tokens from circulation without any authorization check
+    fn _debit(env: &Env, from: &Address, amount: i128) {
+        let from_balance: i128 = Self::_balance(env, from);
+        if from_balance < amount {
+            panic_with_error!(env, Sep0041Error::InsufficientBalance);
+        }
+
+        // we update the states, from balance and the total supply
+        let from_shares: i128 = Self::_shares(env, from);
+        let shares: i128 = Self::_to_shares(env, amount).min(from_shares);
+        Self::_update_shares(env, from, from_shares - shares);
+
+        // get total_shares
+        let total_shares: i128 = Self::_total_shares(env);
         // update total supply
-        Self::_update_total_supply(env, total_supply - amount);
+        Self::_update_total_shares(env, Self::_checked_sub(env, total_shares, shares));
     }
 
-    fn _transfer(env: &Env, from: &Address, to: &Address, amount: i128) {
+    // returns what `to` received once the transfer fee is taken out
+    fn _transfer(env: &Env, from: &Address, to: &Address, amount: i128) -> i128 {
+        Self::_require_authorized(env, from);
+        Self::_require_authorized(env, to);
+
         let from_balance: i128 = Self::_balance(env, from);
-        assert!(from_balance >= amount);
+        if from_balance < amount {
+            panic_with_error!(env, Sep0041Error::InsufficientBalance);
+        }
+        Self::_require_unlocked(env, from, from_balance, amount);
+        let from_shares: i128 = Self::_shares(env, from);
+        let shares: i128 = Self::_to_shares(env, amount).min(from_shares);
+        Self::_update_shares(env, from, from_shares - shares);
 
-        // to balance
-        let to_balance: i128 = Self::_balance(env, to);
+        // credited after the debit so a transfer to self nets out
+        Self::_settle(env, from, to, amount, shares)
+    }
 
-        let from_new_balance: i128 = from_balance - amount;
-        let to_new_balance: i128 = to_balance + amount;
+    // credits `to` with the `shares` worth `amount` already debited from `from`, less the
+    // transfer fee which goes to the fee collector. Returns the net amount
+    fn _settle(env: &Env, from: &Address, to: &Address, amount: i128, shares: i128) -> i128 {
+        let Some((collector, fee)) = Self::_fee_for(env, from, to, amount) else {
+            Self::_credit(env, to, shares);
+            emit_transfer(env, from.clone(), to.clone(), amount);
+            return amount;
+        };
 
-        Self::_update_balance(env, from, from_new_balance);
-        Self::_update_balance(env, to, to_new_balance);
+        // the collector receives like any other account
+        Self::_require_authorized(env, &collector);
+        let fee_shares: i128 = Self::_to_shares(env, fee).min(shares);
+        let net: i128 = amount - fee;
+        Self::_credit(env, to, shares - fee_shares);
+        Self::_credit(env, &collector, fee_shares);
+        emit_transfer(env, from.clone(), to.clone(), net);
+        emit_transfer(env, from.clone(), collector, fee);
+        emit_transfer_fee(env, from.clone(), to.clone(), amount, fee, net);
+        net
     }
 
-    fn _balance(env: &Env, id: &Address) -> i128 {
-        let balance_key: DataKey = DataKey::Balance(id.clone());
-        // log!(env, "balance key {}", balance_key);
-        let result = env.storage().instance().get(&balance_key).unwrap_or(0_i128);
-        log!(env, "we should see here  {}", result);
-        return result;
+    // the collector and the fee owed on a transfer, `None` when it carries no fee
+    fn _fee_for(env: &Env, from: &Address, to: &Address, amount: i128) -> Option<(Address, i128)> {
+        let fee_bps: u32 = Self::_transfer_fee_bps(env);
+        if fee_bps == 0 {
+            return None;
+        }
+        let collector: Address = env.storage().instance().get(&DataKey::FeeCollector)?;
+        if *from == collector
+            || *to == collector
+            || Self::_fee_exempt(env, from)
+            || Self::_fee_exempt(env, to)
+        {
+            return None;
+        }
+
+        let fee: i128 = Self::_bps_of(amount, fee_bps);
+        if fee == 0 {
+            return None;
+        }
+        Some((collector, fee))
+    }
+
+    // rounds down, split up so large amounts cannot overflow
+    fn _bps_of(amount: i128, bps: u32) -> i128 {
+        let bps: i128 = bps as i128;
+        amount / BPS_DENOMINATOR * bps + amount % BPS_DENOMINATOR * bps / BPS_DENOMINATOR
     }
 
-    fn _update_balance(env: &Env, id: &Address, amount: i128) {
+    fn _transfer_fee_bps(env: &Env) -> u32 {
         env.storage()
             .instance()
-            .set(&DataKey::Balance(id.clone()), &amount);
+            .get(&DataKey::TransferFeeBps)
+            .unwrap_or(0)
+    }
+
+    fn _fee_exempt(env: &Env, id: &Address) -> bool {
+        let exempt_key: DataKey = DataKey::FeeExempt(id.clone());
+        if env.storage().persistent().has(&exempt_key) {
+            env.storage().persistent().extend_ttl(
+                &exempt_key,
+                BALANCE_LIFETIME_THRESHOLD,
+                BALANCE_BUMP_AMOUNT,
+            );
+            return true;
+        }
+        false
+    }
+
+    fn _credit(env: &Env, to: &Address, shares: i128) {
+        let to_shares: i128 = Self::_shares(env, to);
+        let to_new_shares: i128 = Self::_checked_add(env, to_shares, shares);
+        Self::_update_shares(env, to, to_new_shares);
+    }
+
+    fn _balance(env: &Env, id: &Address) -> i128 {
+        Self::_to_amount(env, Self::_shares(env, id))
+    }
+
+    fn _shares(env: &Env, id: &Address) -> i128 {
+        let balance_key: DataKey = DataKey::Shares(id.clone());
+        // log!(env, "balance key {}", balance_key);
+        if let Some(result) = env.storage().persistent().get::<_, i128>(&balance_key) {
+            env.storage().persistent().extend_ttl(
+                &balance_key,
+                BALANCE_LIFETIME_THRESHOLD,
+                BALANCE_BUMP_AMOUNT,
+            );
+            log!(env, "we should see here  {}", result);
+            return result;
+        }
+        // written before balances were kept in shares, when the index could not move yet
+        let legacy_balance: i128 = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Balance(id.clone()))
+            .unwrap_or(0);
+        Self::_checked_mul(env, legacy_balance, SHARES_PER_TOKEN)
+    }
+
+    fn _update_shares(env: &Env, id: &Address, shares: i128) {
+        let balance_key: DataKey = DataKey::Shares(id.clone());
+        let previous: i128 = Self::_shares(env, id);
+        Self::_write_checkpoint(
+            env,
+            &DataKey::BalanceCheckpoints(id.clone()),
+            |page| DataKey::BalanceCheckpointPage(id.clone(), page),
+            previous,
+            shares,
+        );
+        // the whole token balance of schema version 1 goes with the first write in shares. It is
+        // only looked up while there are no shares yet, so later writes do not touch it
+        if !env.storage().persistent().has(&balance_key) {
+            let legacy_key: DataKey = DataKey::Balance(id.clone());
+            if env.storage().persistent().has(&legacy_key) {
+                env.storage().persistent().remove(&legacy_key);
+            }
+        }
+        env.storage().persistent().set(&balance_key, &shares);
+        // voting power follows every balance change, whatever caused it
+        if let Some(delegatee) = Self::_delegate(env, id) {
+            Self::_move_votes(env, &delegatee, shares - previous);
+        }
+        env.storage().persistent().extend_ttl(
+            &balance_key,
+            BALANCE_LIFETIME_THRESHOLD,
+            BALANCE_BUMP_AMOUNT,
+        );
         log!(env, "updated balance")
     }
 
@@ -230,31 +1242,783 @@ impl Sep0041 {
         spender: Address,
         tx_details: AllowanaceDetails,
     ) {
-        env.storage().instance().set(
-            &DataKey::Allowance(from.clone(), spender.clone()),
-            &tx_details,
-        );
+        let allowance_key: DataKey = DataKey::Allowance(from, spender);
+        env.storage().temporary().set(&allowance_key, &tx_details);
+        Self::_extend_allowance_ttl(env, &allowance_key, tx_details.expiration_ledger);
         log!(env, "done")
     }
-    fn _allowance(env: &Env, from: &Address, spender: &Address) -> (i128, u64) {
-        let tx_details: AllowanaceDetails = env
+    fn _allowance(env: &Env, from: &Address, spender: &Address) -> (i128, u32) {
+        let allowance_key: DataKey = DataKey::Allowance(from.clone(), spender.clone());
+        // an allowance that was never set or whose entry has been archived reads as zero
+        let Some(tx_details) = env
+            .storage()
+            .temporary()
+            .get::<_, AllowanaceDetails>(&allowance_key)
+        else {
+            return (0, 0);
+        };
+        // the allowance is usable up to and including its expiration ledger
+        if tx_details.expiration_ledger < Self::_current_ledger(env) {
+            return (0, tx_details.expiration_ledger);
+        }
+        Self::_extend_allowance_ttl(env, &allowance_key, tx_details.expiration_ledger);
+        (tx_details.amount, tx_details.expiration_ledger)
+    }
+
+    fn _approve(
+        env: &Env,
+        from: &Address,
+        spender: &Address,
+        amount: i128,
+        expiration_ledger: u32,
+    ) {
+        if amount < 0 {
+            panic_with_error!(env, Sep0041Error::InvalidAmount);
+        }
+        // zero revokes, the entry is dropped instead of kept around as an empty allowance
+        if amount == 0 {
+            Self::_remove_allowance(env, from, spender);
+            emit_approve(env, from.clone(), spender.clone(), 0, expiration_ledger);
+            return;
+        }
+        let current_ledger: u32 = Self::_current_ledger(env);
+        // the temporary entry cannot outlive the network's maximum ttl, as in the stellar
+        // asset contract
+        let max_expiration_ledger: u32 = current_ledger.saturating_add(env.storage().max_ttl());
+        if expiration_ledger < current_ledger || expiration_ledger > max_expiration_ledger {
+            panic_with_error!(env, Sep0041Error::InvalidExpirationLedger);
+        }
+        Self::_index_spender(env, from, spender, expiration_ledger);
+
+        //now create the details and save
+        let tx_details: AllowanaceDetails =
+            Self::_create_allowance_details(amount, expiration_ledger);
+        Self::_update_allowance(env, from.clone(), spender.clone(), tx_details);
+
+        emit_approve(
+            env,
+            from.clone(),
+            spender.clone(),
+            amount,
+            expiration_ledger,
+        );
+    }
+
+    fn _permit_nonce(env: &Env, owner: &Address) -> u64 {
+        let nonce_key: DataKey = DataKey::PermitNonce(owner.clone());
+        if let Some(nonce) = env.storage().persistent().get::<_, u64>(&nonce_key) {
+            env.storage().persistent().extend_ttl(
+                &nonce_key,
+                BALANCE_LIFETIME_THRESHOLD,
+                BALANCE_BUMP_AMOUNT,
+            );
+            return nonce;
+        }
+        0
+    }
+
+    fn _permit_enabled(env: &Env, owner: &Address) -> bool {
+        let enabled_key: DataKey = DataKey::PermitEnabled(owner.clone());
+        if env.storage().persistent().has(&enabled_key) {
+            env.storage().persistent().extend_ttl(
+                &enabled_key,
+                BALANCE_LIFETIME_THRESHOLD,
+                BALANCE_BUMP_AMOUNT,
+            );
+            return true;
+        }
+        false
+    }
+
+    fn _update_permit_nonce(env: &Env, owner: &Address, nonce: u64) {
+        let nonce_key: DataKey = DataKey::PermitNonce(owner.clone());
+        env.storage().persistent().set(&nonce_key, &nonce);
+        env.storage().persistent().extend_ttl(
+            &nonce_key,
+            BALANCE_LIFETIME_THRESHOLD,
+            BALANCE_BUMP_AMOUNT,
+        );
+    }
+
+    fn _spend_allowance(env: &Env, from: &Address, spender: &Address, amount: i128) {
+        let tx_details: AllowanaceDetails = Self::_live_allowance(env, from, spender);
+        if tx_details.amount < amount {
+            panic_with_error!(env, Sep0041Error::InsufficientAllowance);
+        }
+        // left as it is, which also saves the write
+        if tx_details.amount == UNLIMITED_ALLOWANCE {
+            return;
+        }
+        if tx_details.amount == amount {
+            Self::_remove_allowance(env, from, spender);
+            return;
+        }
+
+        let tx_details: AllowanaceDetails = Self::_create_allowance_details(
+            tx_details.amount - amount,
+            tx_details.expiration_ledger,
+        );
+        Self::_update_allowance(env, from.clone(), spender.clone(), tx_details);
+    }
+
+    // adds `delta` to a live allowance, keeping its expiration. Reaching zero revokes it
+    fn _adjust_allowance(env: &Env, from: &Address, spender: &Address, delta: i128) {
+        let tx_details: AllowanaceDetails = Self::_live_allowance(env, from, spender);
+        let amount: i128 = Self::_checked_add(env, tx_details.amount, delta);
+        if amount < 0 {
+            panic_with_error!(env, Sep0041Error::InsufficientAllowance);
+        }
+        Self::_approve(env, from, spender, amount, tx_details.expiration_ledger);
+    }
+
+    // the stored allowance, failing when there is none or it has expired
+    fn _live_allowance(env: &Env, from: &Address, spender: &Address) -> AllowanaceDetails {
+        let Some(tx_details) = env
             .storage()
+            .temporary()
+            .get::<_, AllowanaceDetails>(&DataKey::Allowance(from.clone(), spender.clone()))
+        else {
+            panic_with_error!(env, Sep0041Error::InsufficientAllowance);
+        };
+        if tx_details.expiration_ledger < Self::_current_ledger(env) {
+            panic_with_error!(env, Sep0041Error::AllowanceExpired);
+        }
+        tx_details
+    }
+
+    fn _remove_allowance(env: &Env, from: &Address, spender: &Address) {
+        env.storage()
+            .temporary()
+            .remove(&DataKey::Allowance(from.clone(), spender.clone()));
+
+        let mut spenders: Vec<Address> = Self::_spenders(env, from);
+        if let Some(index) = spenders.first_index_of(spender) {
+            spenders.remove(index);
+            Self::_update_spenders(env, from, &spenders);
+        }
+    }
+
+    fn _spenders(env: &Env, owner: &Address) -> Vec<Address> {
+        let spenders_key: DataKey = DataKey::Spenders(owner.clone());
+        if let Some(spenders) = env
+            .storage()
+            .persistent()
+            .get::<_, Vec<Address>>(&spenders_key)
+        {
+            env.storage().persistent().extend_ttl(
+                &spenders_key,
+                BALANCE_LIFETIME_THRESHOLD,
+                BALANCE_BUMP_AMOUNT,
+            );
+            return spenders;
+        }
+        Vec::new(env)
+    }
+
+    fn _update_spenders(env: &Env, owner: &Address, spenders: &Vec<Address>) {
+        let spenders_key: DataKey = DataKey::Spenders(owner.clone());
+        if spenders.is_empty() {
+            env.storage().persistent().remove(&spenders_key);
+            return;
+        }
+        env.storage().persistent().set(&spenders_key, spenders);
+        env.storage().persistent().extend_ttl(
+            &spenders_key,
+            BALANCE_LIFETIME_THRESHOLD,
+            BALANCE_BUMP_AMOUNT,
+        );
+    }
+
+    fn _index_spender(env: &Env, owner: &Address, spender: &Address, expiration_ledger: u32) {
+        let mut spenders: Vec<Address> = Self::_spenders(env, owner);
+        if spenders.contains(spender) {
+            return;
+        }
+        if spenders.len() >= MAX_SPENDERS {
+            // only a full index is pruned, that takes a read per spender
+            let mut live_spenders: Vec<Address> = Vec::new(env);
+            for indexed in spenders.iter() {
+                if Self::_allowance(env, owner, &indexed).0 > 0 {
+                    live_spenders.push_back(indexed);
+                }
+            }
+            // the index only serves `allowances_of`, the approval stands without it
+            if live_spenders.len() >= MAX_SPENDERS {
+                Self::_add_unlisted(env, owner, expiration_ledger);
+                return;
+            }
+            spenders = live_spenders;
+        }
+        spenders.push_back(spender.clone());
+        Self::_update_spenders(env, owner, &spenders);
+    }
+
+    fn _add_unlisted(env: &Env, owner: &Address, expiration_ledger: u32) {
+        let unlisted_key: DataKey = DataKey::UnlistedUntil(owner.clone());
+        let unlisted_until: u32 = env.storage().persistent().get(&unlisted_key).unwrap_or(0);
+        env.storage()
+            .persistent()
+            .set(&unlisted_key, &unlisted_until.max(expiration_ledger));
+        env.storage().persistent().extend_ttl(
+            &unlisted_key,
+            BALANCE_LIFETIME_THRESHOLD,
+            BALANCE_BUMP_AMOUNT,
+        );
+    }
+
+    // whether an approval left out of the index of `owner` may still be live, the mark is
+    // dropped once none can be
+    fn _has_unlisted(env: &Env, owner: &Address) -> bool {
+        let unlisted_key: DataKey = DataKey::UnlistedUntil(owner.clone());
+        let Some(unlisted_until) = env.storage().persistent().get::<_, u32>(&unlisted_key) else {
+            return false;
+        };
+        if unlisted_until < Self::_current_ledger(env) {
+            env.storage().persistent().remove(&unlisted_key);
+            return false;
+        }
+        env.storage().persistent().extend_ttl(
+            &unlisted_key,
+            BALANCE_LIFETIME_THRESHOLD,
+            BALANCE_BUMP_AMOUNT,
+        );
+        true
+    }
+
+    // allowances are temporary entries that only need to live until they expire
+    fn _extend_allowance_ttl(env: &Env, allowance_key: &DataKey, expiration_ledger: u32) {
+        let current_ledger: u32 = Self::_current_ledger(env);
+        if expiration_ledger < current_ledger {
+            return;
+        }
+        let live_for: u32 = expiration_ledger - current_ledger;
+        env.storage()
+            .temporary()
+            .extend_ttl(allowance_key, live_for, live_for);
+    }
+
+    fn _extend_instance_ttl(env: &Env) {
+        env.storage()
             .instance()
-            .get(&DataKey::Allowance(from.clone(), spender.clone()))
-            .unwrap();
-        (tx_details.amount, tx_details.deadline)
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
     }
 
-    fn _total_supply(env: &Env) -> i128 {
+    fn _authorized(env: &Env, id: &Address) -> bool {
+        let authorized_key: DataKey = DataKey::Authorized(id.clone());
+        if let Some(authorized) = env.storage().persistent().get::<_, bool>(&authorized_key) {
+            env.storage().persistent().extend_ttl(
+                &authorized_key,
+                BALANCE_LIFETIME_THRESHOLD,
+                BALANCE_BUMP_AMOUNT,
+            );
+            return authorized;
+        }
+        // accounts the admin never touched follow the mode chosen at construction
+        let auth_required: bool = env
+            .storage()
+            .instance()
+            .get(&DataKey::AuthRequired)
+            .unwrap_or(false);
+        !auth_required
+    }
+
+    fn _require_authorized(env: &Env, id: &Address) {
+        if !Self::_authorized(env, id) {
+            panic_with_error!(env, Sep0041Error::Unauthorized);
+        }
+    }
+
+    fn _minter(env: &Env, id: &Address) -> Option<MinterDetails> {
+        let minter_key: DataKey = DataKey::Minter(id.clone());
+        let minter_details: Option<MinterDetails> = env.storage().persistent().get(&minter_key);
+        if minter_details.is_some() {
+            env.storage().persistent().extend_ttl(
+                &minter_key,
+                BALANCE_LIFETIME_THRESHOLD,
+                BALANCE_BUMP_AMOUNT,
+            );
+        }
+        minter_details
+    }
+
+    fn _update_minter(env: &Env, id: &Address, minter_details: &MinterDetails) {
+        let minter_key: DataKey = DataKey::Minter(id.clone());
+        env.storage().persistent().set(&minter_key, minter_details);
+        env.storage().persistent().extend_ttl(
+            &minter_key,
+            BALANCE_LIFETIME_THRESHOLD,
+            BALANCE_BUMP_AMOUNT,
+        );
+    }
+
+    fn _schema_version(env: &Env) -> u32 {
+        // deployments from before versioning was introduced have no version stored
         env.storage()
             .instance()
-            .get(&DataKey::TotalSupply)
+            .get(&DataKey::SchemaVersion)
             .unwrap_or(0)
     }
 
-    fn _update_total_supply(env: &Env, total_supply: i128) {
+    fn _migrate_legacy_metadata(env: &Env) {
+        let instance = env.storage().instance();
+        let name: Option<String> = instance.get(&LegacyDataKey::Name);
+        let symbol: Option<String> = instance.get(&LegacyDataKey::Symbol);
+        let decimal: Option<u32> = instance.get(&LegacyDataKey::Decimal);
+        if let (Some(name), Some(symbol), Some(decimal)) = (name, symbol, decimal) {
+            // written as is, legacy metadata predates validation
+            instance.set(
+                &METADATA_KEY,
+                &TokenMetadata {
+                    decimal,
+                    name,
+                    symbol,
+                },
+            );
+            instance.remove(&LegacyDataKey::Name);
+            instance.remove(&LegacyDataKey::Symbol);
+            instance.remove(&LegacyDataKey::Decimal);
+        }
+    }
+
+    fn _migrate_legacy_balance(env: &Env, holder: &Address) {
+        let legacy_key: LegacyDataKey = LegacyDataKey::Balance(holder.clone());
+        if let Some(legacy_balance) = env.storage().instance().get::<_, i128>(&legacy_key) {
+            // whole tokens like the legacy supply, which is read the same way
+            let shares: i128 = Self::_checked_add(
+                env,
+                Self::_shares(env, holder),
+                Self::_checked_mul(env, legacy_balance, SHARES_PER_TOKEN),
+            );
+            Self::_update_shares(env, holder, shares);
+            env.storage().instance().remove(&legacy_key);
+        }
+    }
+
+    fn _migrate_legacy_allowance(env: &Env, owner: &Address, spender: &Address) {
+        let legacy_key: LegacyDataKey = LegacyDataKey::Allowance(owner.clone(), spender.clone());
+        let Some(legacy) = env
+            .storage()
+            .instance()
+            .get::<_, LegacyAllowanceDetails>(&legacy_key)
+        else {
+            return;
+        };
+        env.storage().instance().remove(&legacy_key);
+
+        // the legacy layout allowed spending up to and including the deadline
+        let now: u64 = env.ledger().timestamp();
+        if legacy.amount <= 0 || legacy.deadline < now {
+            return;
+        }
+        let ledgers_left: u64 =
+            ((legacy.deadline - now) / LEDGER_SECONDS).min(env.storage().max_ttl() as u64);
+        let expiration_ledger: u32 = Self::_current_ledger(env) + ledgers_left as u32;
+        Self::_approve(env, owner, spender, legacy.amount, expiration_ledger);
+    }
+
+    fn _underlying(env: &Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::Underlying)
+    }
+
+    fn _underlying_client(env: &Env) -> TokenClient<'_> {
+        let underlying: Address = Self::_underlying(env)
+            .unwrap_or_else(|| panic_with_error!(env, Sep0041Error::NotWrapped));
+        TokenClient::new(env, &underlying)
+    }
+
+    // the supply of a wrapper only grows through `deposit`, anything else would leave it
+    // short of reserves. Holders can still burn, which leaves the reserves above the supply
+    fn _require_unwrapped(env: &Env) {
+        if Self::_underlying(env).is_some() {
+            panic_with_error!(env, Sep0041Error::WrappedSupply);
+        }
+    }
+
+    // every wrapped token stays redeemable. Reserves can exceed the supply when someone
+    // sends the underlying token straight to the contract, that surplus is never minted
+    fn _require_backed(env: &Env, underlying: &TokenClient) {
+        let reserves: i128 = underlying.balance(&env.current_contract_address());
+        if reserves < Self::_total_supply(env) {
+            panic_with_error!(env, Sep0041Error::InsufficientReserves);
+        }
+    }
+
+    fn _max_supply(env: &Env) -> Option<i128> {
+        env.storage().instance().get(&DataKey::MaxSupply)
+    }
+
+    fn _paused(env: &Env) -> bool {
+        env.storage()
+            .instance()
+            .get(&DataKey::Paused)
+            .unwrap_or(false)
+    }
+
+    fn _require_not_paused(env: &Env) {
+        if Self::_paused(env) {
+            panic_with_error!(env, Sep0041Error::Paused);
+        }
+    }
+
+    fn _require_admin_or_pauser(env: &Env, caller: &Address) {
+        caller.require_auth();
+        let is_admin: bool =
+            env.storage().instance().get::<_, Address>(&DataKey::Admin) == Some(caller.clone());
+        let is_pauser: bool =
+            env.storage().instance().get::<_, Address>(&DataKey::Pauser) == Some(caller.clone());
+        if !is_admin && !is_pauser {
+            panic_with_error!(env, Sep0041Error::Unauthorized);
+        }
+    }
+
+    fn _require_admin_or_oracle(env: &Env, caller: &Address) {
+        caller.require_auth();
+        let is_admin: bool =
+            env.storage().instance().get::<_, Address>(&DataKey::Admin) == Some(caller.clone());
+        let is_oracle: bool = env
+            .storage()
+            .instance()
+            .get::<_, Address>(&DataKey::RebaseOracle)
+            == Some(caller.clone());
+        if !is_admin && !is_oracle {
+            panic_with_error!(env, Sep0041Error::Unauthorized);
+        }
+    }
+
+    fn _total_supply(env: &Env) -> i128 {
+        Self::_to_amount(env, Self::_total_shares(env))
+    }
+
+    fn _total_shares(env: &Env) -> i128 {
+        if let Some(total_shares) = env.storage().instance().get(&DataKey::TotalShares) {
+            return total_shares;
+        }
+        let legacy_supply: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::TotalSupply)
+            .unwrap_or(0);
+        Self::_checked_mul(env, legacy_supply, SHARES_PER_TOKEN)
+    }
+
+    fn _update_total_shares(env: &Env, total_shares: i128) {
+        Self::_write_checkpoint(
+            env,
+            &DataKey::SupplyCheckpoints,
+            DataKey::SupplyCheckpointPage,
+            Self::_total_shares(env),
+            total_shares,
+        );
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalShares, &(total_shares));
+        env.storage().instance().remove(&DataKey::TotalSupply);
+    }
+
+    fn _index(env: &Env) -> i128 {
         env.storage()
             .instance()
-            .set(&DataKey::TotalSupply, &(total_supply));
+            .get(&DataKey::RebaseIndex)
+            .unwrap_or(REBASE_INDEX_ONE)
+    }
+
+    // the index in force at the end of `ledger`
+    fn _index_at(env: &Env, ledger: u32) -> i128 {
+        Self::_checkpoint_lookup(
+            env,
+            &DataKey::IndexCheckpoints,
+            DataKey::IndexCheckpointPage,
+            ledger,
+        )
+        .unwrap_or_else(|| Self::_index(env))
+    }
+
+    // both ways round to the nearest unit. A share is far below a token unit, so the
+    // rounding of a single move never shows up in a balance. Whatever leaves an account is
+    // capped at its shares, so rounding never creates tokens
+    fn _to_amount(env: &Env, shares: i128) -> i128 {
+        Self::_to_amount_with(env, shares, Self::_index(env))
+    }
+
+    fn _to_amount_at(env: &Env, shares: i128, ledger: u32) -> i128 {
+        Self::_to_amount_with(env, shares, Self::_index_at(env, ledger))
+    }
+
+    fn _to_amount_with(env: &Env, shares: i128, index: i128) -> i128 {
+        Self::_mul_div(env, shares, index, REBASE_INDEX_ONE * SHARES_PER_TOKEN)
+    }
+
+    fn _to_shares(env: &Env, amount: i128) -> i128 {
+        Self::_mul_div(
+            env,
+            amount,
+            REBASE_INDEX_ONE * SHARES_PER_TOKEN,
+            Self::_index(env),
+        )
+    }
+
+    // `a * b / d` rounded half up for non negative operands, split up like `_bps_of` so
+    // large balances cannot overflow
+    fn _mul_div(env: &Env, a: i128, b: i128, d: i128) -> i128 {
+        let whole: i128 = Self::_checked_mul(env, a / d, b);
+        let remainder: i128 = Self::_checked_mul(env, a % d, b);
+        let mut part: i128 = remainder / d;
+        if remainder % d >= d - remainder % d {
+            part += 1;
+        }
+        Self::_checked_add(env, whole, part)
+    }
+
+    fn _require_past_ledger(env: &Env, ledger: u32) {
+        // the current ledger can still change after this call
+        if ledger >= Self::_current_ledger(env) {
+            panic_with_error!(env, Sep0041Error::FutureLookup);
+        }
+    }
+
+    fn _checkpoints(env: &Env, key: &DataKey) -> Checkpoints {
+        if let Some(checkpoints) = env.storage().persistent().get::<_, Checkpoints>(key) {
+            env.storage().persistent().extend_ttl(
+                key,
+                BALANCE_LIFETIME_THRESHOLD,
+                BALANCE_BUMP_AMOUNT,
+            );
+            return checkpoints;
+        }
+        Checkpoints {
+            pages: 0,
+            recent: Vec::new(env),
+        }
+    }
+
+    fn _checkpoint_page(env: &Env, key: &DataKey) -> Vec<Checkpoint> {
+        let page: Vec<Checkpoint> = env
+            .storage()
+            .persistent()
+            .get(key)
+            .unwrap_or_else(|| panic_with_error!(env, Sep0041Error::NotInitialized));
+        env.storage()
+            .persistent()
+            .extend_ttl(key, BALANCE_LIFETIME_THRESHOLD, BALANCE_BUMP_AMOUNT);
+        page
+    }
+
+    // records `amount` for the current ledger, several updates within one ledger share a
+    // checkpoint. `previous` is the value before this update
+    fn _write_checkpoint(
+        env: &Env,
+        key: &DataKey,
+        page_key: impl Fn(u32) -> DataKey,
+        previous: i128,
+        amount: i128,
+    ) {
+        let ledger: u32 = Self::_current_ledger(env);
+        let mut checkpoints: Checkpoints = Self::_checkpoints(env, key);
+
+        if checkpoints.pages == 0 && checkpoints.recent.is_empty() && previous != 0 {
+            // a value from before checkpoints existed, its history starts at ledger 0
+            checkpoints.recent.push_back(Checkpoint {
+                ledger: 0,
+                amount: previous,
+            });
+        }
+
+        let checkpoint: Checkpoint = Checkpoint { ledger, amount };
+        match checkpoints.recent.last() {
+            Some(last) if last.ledger == ledger => {
+                checkpoints
+                    .recent
+                    .set(checkpoints.recent.len() - 1, checkpoint);
+            }
+            _ => {
+                if checkpoints.recent.len() == CHECKPOINT_PAGE_SIZE {
+                    let full_key: DataKey = page_key(checkpoints.pages);
+                    env.storage()
+                        .persistent()
+                        .set(&full_key, &checkpoints.recent);
+                    env.storage().persistent().extend_ttl(
+                        &full_key,
+                        BALANCE_LIFETIME_THRESHOLD,
+                        BALANCE_BUMP_AMOUNT,
+                    );
+                    checkpoints.pages += 1;
+                    checkpoints.recent = Vec::new(env);
+                }
+                checkpoints.recent.push_back(checkpoint);
+            }
+        }
+
+        env.storage().persistent().set(key, &checkpoints);
+        env.storage()
+            .persistent()
+            .extend_ttl(key, BALANCE_LIFETIME_THRESHOLD, BALANCE_BUMP_AMOUNT);
+    }
+
+    // the amount of the last checkpoint at or before `ledger`, `None` when nothing has been
+    // recorded yet
+    fn _checkpoint_lookup(
+        env: &Env,
+        key: &DataKey,
+        page_key: impl Fn(u32) -> DataKey,
+        ledger: u32,
+    ) -> Option<i128> {
+        let checkpoints: Checkpoints = Self::_checkpoints(env, key);
+        // lookups of recent ledgers are the common case
+        let first: Checkpoint = checkpoints.recent.first()?;
+        if first.ledger <= ledger {
+            return Self::_last_at(&checkpoints.recent, ledger);
+        }
+
+        // first page that starts after `ledger`, the one before it holds the answer
+        let mut low: u32 = 0;
+        let mut high: u32 = checkpoints.pages;
+        while low < high {
+            let mid: u32 = low + (high - low) / 2;
+            let page: Vec<Checkpoint> = Self::_checkpoint_page(env, &page_key(mid));
+            if page.first_unchecked().ledger > ledger {
+                high = mid;
+            } else {
+                low = mid + 1;
+            }
+        }
+
+        if low == 0 {
+            return Some(0);
+        }
+        let page: Vec<Checkpoint> = Self::_checkpoint_page(env, &page_key(low - 1));
+        Self::_last_at(&page, ledger)
+    }
+
+    // the amount of the last of `checkpoints` at or before `ledger`
+    fn _last_at(checkpoints: &Vec<Checkpoint>, ledger: u32) -> Option<i128> {
+        checkpoints
+            .iter()
+            .rev()
+            .find(|checkpoint| checkpoint.ledger <= ledger)
+            .map(|checkpoint| checkpoint.amount)
+    }
+
+    fn _delegate(env: &Env, id: &Address) -> Option<Address> {
+        let delegate_key: DataKey = DataKey::Delegate(id.clone());
+        let delegatee: Option<Address> = env.storage().persistent().get(&delegate_key);
+        if delegatee.is_some() {
+            env.storage().persistent().extend_ttl(
+                &delegate_key,
+                BALANCE_LIFETIME_THRESHOLD,
+                BALANCE_BUMP_AMOUNT,
+            );
+        }
+        delegatee
+    }
+
+    fn _votes(env: &Env, id: &Address) -> i128 {
+        Self::_checkpoints(env, &DataKey::VotesCheckpoints(id.clone()))
+            .recent
+            .last()
+            .map_or(0, |checkpoint| checkpoint.amount)
+    }
+
+    fn _move_votes(env: &Env, delegatee: &Address, delta: i128) {
+        if delta == 0 {
+            return;
+        }
+        let previous: i128 = Self::_votes(env, delegatee);
+        let votes: i128 = Self::_checked_add(env, previous, delta);
+        Self::_write_checkpoint(
+            env,
+            &DataKey::VotesCheckpoints(delegatee.clone()),
+            |page| DataKey::VotesCheckpointPage(delegatee.clone(), page),
+            previous,
+            votes,
+        );
+        emit_delegate_votes_changed(
+            env,
+            delegatee.clone(),
+            Self::_to_amount(env, previous),
+            Self::_to_amount(env, votes),
+        );
+    }
+
+    fn _locks(env: &Env, id: &Address) -> Vec<Lock> {
+        let locks_key: DataKey = DataKey::Locks(id.clone());
+        if let Some(locks) = env.storage().persistent().get::<_, Vec<Lock>>(&locks_key) {
+            env.storage().persistent().extend_ttl(
+                &locks_key,
+                BALANCE_LIFETIME_THRESHOLD,
+                BALANCE_BUMP_AMOUNT,
+            );
+            return locks;
+        }
+        Vec::new(env)
+    }
+
+    fn _update_locks(env: &Env, id: &Address, locks: &Vec<Lock>) {
+        let locks_key: DataKey = DataKey::Locks(id.clone());
+        if locks.is_empty() {
+            env.storage().persistent().remove(&locks_key);
+            return;
+        }
+        env.storage().persistent().set(&locks_key, locks);
+        env.storage().persistent().extend_ttl(
+            &locks_key,
+            BALANCE_LIFETIME_THRESHOLD,
+            BALANCE_BUMP_AMOUNT,
+        );
+    }
+
+    // the sum of the locks that have not reached their unlock ledger yet
+    fn _locked(env: &Env, id: &Address) -> i128 {
+        let current_ledger: u32 = Self::_current_ledger(env);
+        let mut locked: i128 = 0;
+        for lock in Self::_locks(env, id).iter() {
+            if lock.unlock_ledger > current_ledger {
+                locked = Self::_checked_add(env, locked, lock.amount);
+            }
+        }
+        locked
+    }
+
+    // spending `amount` out of `balance` must leave the locked tokens behind
+    fn _require_unlocked(env: &Env, id: &Address, balance: i128, amount: i128) {
+        if balance >= amount && balance - amount < Self::_locked(env, id) {
+            panic_with_error!(env, Sep0041Error::BalanceLocked);
+        }
+    }
+
+    fn _add_lock(env: &Env, id: &Address, amount: i128, unlock_ledger: u32) {
+        if unlock_ledger <= Self::_current_ledger(env) {
+            panic_with_error!(env, Sep0041Error::InvalidUnlockLedger);
+        }
+        // expired locks make room for the new one
+        Self::_release_expired(env, id);
+        let mut locks: Vec<Lock> = Self::_locks(env, id);
+        if locks.len() >= MAX_LOCKS {
+            panic_with_error!(env, Sep0041Error::TooManyLocks);
+        }
+        locks.push_back(Lock {
+            amount,
+            unlock_ledger,
+        });
+        Self::_update_locks(env, id, &locks);
+
+        emit_lock(env, id.clone(), amount, unlock_ledger);
+    }
+
+    fn _release_expired(env: &Env, id: &Address) -> i128 {
+        let current_ledger: u32 = Self::_current_ledger(env);
+        let locks: Vec<Lock> = Self::_locks(env, id);
+        let mut remaining: Vec<Lock> = Vec::new(env);
+        let mut released: i128 = 0;
+        for lock in locks.iter() {
+            if lock.unlock_ledger > current_ledger {
+                remaining.push_back(lock);
+            } else {
+                released = Self::_checked_add(env, released, lock.amount);
+            }
+        }
+        if remaining.len() != locks.len() {
+            Self::_update_locks(env, id, &remaining);
+        }
+        released
     }
 }
@@ -1,7 +1,42 @@
 use soroban_sdk::contracterror;
 
 #[contracterror]
-#[derive(Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
 pub enum Sep0041Error {
     InsufficientBalance = 1,
+    InsufficientAllowance = 2,
+    AllowanceExpired = 3,
+    InvalidAmount = 4,
+    NotInitialized = 5,
+    Overflow = 6,
+    Unauthorized = 7,
+    InvalidExpirationLedger = 8,
+    NoAdmin = 9,
+    NoPendingAdmin = 10,
+    PendingAdminExpired = 11,
+    Paused = 12,
+    MaxSupplyExceeded = 13,
+    InvalidMaxSupply = 14,
+    InvalidDecimals = 15,
+    InvalidName = 16,
+    InvalidSymbol = 17,
+    NotMinter = 18,
+    MinterQuotaExceeded = 19,
+    InvalidMinterConfig = 20,
+    InvalidNonce = 21,
+    EmptyBatch = 22,
+    DuplicateRecipient = 23,
+    BatchTooLarge = 24,
+    FutureLookup = 25,
+    ReceiverRejected = 26,
+    InvalidFee = 27,
+    InvalidUnlockLedger = 28,
+    BalanceLocked = 29,
+    TooManyLocks = 30,
+    NotWrapped = 31,
+    WrappedSupply = 32,
+    InsufficientReserves = 33,
+    InvalidRebaseIndex = 34,
+    PermitNotEnabled = 35,
 }
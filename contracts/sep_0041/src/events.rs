@@ -1,7 +1,203 @@
-use soroban_sdk::{contracttype, Address};
+pub use self::sep_0041_events::{
+    emit_add_minter, emit_admin_proposed, emit_admin_renounced, emit_approve, emit_burn,
+    emit_clawback, emit_delegate_changed, emit_delegate_votes_changed, emit_lock, emit_migrate,
+    emit_mint, emit_paused, emit_rebase, emit_release, emit_remove_minter, emit_set_admin,
+    emit_set_authorized, emit_set_fee_exempt, emit_set_max_supply, emit_set_metadata,
+    emit_set_pauser, emit_set_permit_enabled, emit_set_rebase_oracle, emit_set_transfer_fee,
+    emit_transfer, emit_transfer_fee, emit_unpaused, emit_upgrade,
+};
 
-#[contracttype]
-pub struct Approve {
-    from: Address,
-    spender: Address,
+mod sep_0041_events {
+    use soroban_sdk::{symbol_short, Address, BytesN, Env, String, Symbol};
+
+    // topics and data follow the layout defined by SEP-41
+
+    pub fn emit_transfer(env: &Env, from: Address, to: Address, amount: i128) {
+        env.events()
+            .publish((symbol_short!("transfer"), from, to), amount);
+    }
+
+    // accompanies the transfer events of a transfer that paid a fee
+    pub fn emit_transfer_fee(
+        env: &Env,
+        from: Address,
+        to: Address,
+        gross: i128,
+        fee: i128,
+        net: i128,
+    ) {
+        env.events().publish(
+            (Symbol::new(env, "transfer_fee"), from, to),
+            (gross, fee, net),
+        );
+    }
+
+    pub fn emit_mint(env: &Env, admin: Address, to: Address, amount: i128) {
+        env.events()
+            .publish((symbol_short!("mint"), admin, to), amount);
+    }
+
+    pub fn emit_burn(env: &Env, from: Address, amount: i128) {
+        env.events().publish((symbol_short!("burn"), from), amount);
+    }
+
+    pub fn emit_approve(
+        env: &Env,
+        from: Address,
+        spender: Address,
+        amount: i128,
+        live_until_ledger: u32,
+    ) {
+        env.events().publish(
+            (symbol_short!("approve"), from, spender),
+            (amount, live_until_ledger),
+        );
+    }
+
+    pub fn emit_admin_proposed(
+        env: &Env,
+        admin: Address,
+        new_admin: Address,
+        live_until_ledger: u32,
+    ) {
+        env.events().publish(
+            (Symbol::new(env, "propose_admin"), admin),
+            (new_admin, live_until_ledger),
+        );
+    }
+
+    // same layout as the stellar asset contract `set_admin` event
+    pub fn emit_set_admin(env: &Env, admin: Address, new_admin: Address) {
+        env.events()
+            .publish((symbol_short!("set_admin"), admin), new_admin);
+    }
+
+    pub fn emit_set_authorized(env: &Env, admin: Address, id: Address, authorize: bool) {
+        env.events()
+            .publish((Symbol::new(env, "set_authorized"), admin, id), authorize);
+    }
+
+    pub fn emit_clawback(env: &Env, admin: Address, from: Address, amount: i128) {
+        env.events()
+            .publish((symbol_short!("clawback"), admin, from), amount);
+    }
+
+    pub fn emit_set_pauser(env: &Env, admin: Address, pauser: Address) {
+        env.events()
+            .publish((Symbol::new(env, "set_pauser"), admin), pauser);
+    }
+
+    pub fn emit_paused(env: &Env, caller: Address) {
+        env.events().publish((symbol_short!("paused"), caller), ());
+    }
+
+    pub fn emit_unpaused(env: &Env, caller: Address) {
+        env.events()
+            .publish((symbol_short!("unpaused"), caller), ());
+    }
+
+    pub fn emit_set_max_supply(env: &Env, admin: Address, max_supply: i128) {
+        env.events()
+            .publish((Symbol::new(env, "set_max_supply"), admin), max_supply);
+    }
+
+    pub fn emit_set_metadata(env: &Env, admin: Address, name: String, symbol: String) {
+        env.events()
+            .publish((Symbol::new(env, "set_metadata"), admin), (name, symbol));
+    }
+
+    pub fn emit_add_minter(
+        env: &Env,
+        admin: Address,
+        minter: Address,
+        quota: i128,
+        period_ledgers: u32,
+    ) {
+        env.events().publish(
+            (Symbol::new(env, "add_minter"), admin, minter),
+            (quota, period_ledgers),
+        );
+    }
+
+    pub fn emit_remove_minter(env: &Env, admin: Address, minter: Address) {
+        env.events()
+            .publish((Symbol::new(env, "remove_minter"), admin, minter), ());
+    }
+
+    pub fn emit_upgrade(env: &Env, admin: Address, new_wasm_hash: BytesN<32>) {
+        env.events()
+            .publish((symbol_short!("upgrade"), admin), new_wasm_hash);
+    }
+
+    pub fn emit_migrate(env: &Env, admin: Address, from_version: u32, to_version: u32) {
+        env.events().publish(
+            (symbol_short!("migrate"), admin),
+            (from_version, to_version),
+        );
+    }
+
+    pub fn emit_delegate_changed(
+        env: &Env,
+        delegator: Address,
+        from_delegate: Option<Address>,
+        to_delegate: Address,
+    ) {
+        env.events().publish(
+            (symbol_short!("delegate"), delegator),
+            (from_delegate, to_delegate),
+        );
+    }
+
+    pub fn emit_delegate_votes_changed(
+        env: &Env,
+        delegate: Address,
+        previous_votes: i128,
+        new_votes: i128,
+    ) {
+        env.events().publish(
+            (Symbol::new(env, "delegate_votes_changed"), delegate),
+            (previous_votes, new_votes),
+        );
+    }
+
+    pub fn emit_set_transfer_fee(env: &Env, admin: Address, fee_bps: u32, collector: Address) {
+        env.events().publish(
+            (Symbol::new(env, "set_transfer_fee"), admin),
+            (fee_bps, collector),
+        );
+    }
+
+    pub fn emit_set_fee_exempt(env: &Env, admin: Address, id: Address, exempt: bool) {
+        env.events()
+            .publish((Symbol::new(env, "set_fee_exempt"), admin, id), exempt);
+    }
+
+    pub fn emit_lock(env: &Env, id: Address, amount: i128, unlock_ledger: u32) {
+        env.events()
+            .publish((symbol_short!("lock"), id), (amount, unlock_ledger));
+    }
+
+    pub fn emit_release(env: &Env, id: Address, amount: i128) {
+        env.events().publish((symbol_short!("release"), id), amount);
+    }
+
+    pub fn emit_set_permit_enabled(env: &Env, owner: Address, enabled: bool) {
+        env.events()
+            .publish((Symbol::new(env, "set_permit_enabled"), owner), enabled);
+    }
+
+    pub fn emit_set_rebase_oracle(env: &Env, admin: Address, oracle: Address) {
+        env.events()
+            .publish((Symbol::new(env, "set_rebase_oracle"), admin), oracle);
+    }
+
+    pub fn emit_rebase(env: &Env, caller: Address, previous_index: i128, index: i128) {
+        env.events()
+            .publish((symbol_short!("rebase"), caller), (previous_index, index));
+    }
+
+    pub fn emit_admin_renounced(env: &Env, admin: Address) {
+        env.events()
+            .publish((Symbol::new(env, "renounce_admin"), admin), ());
+    }
 }
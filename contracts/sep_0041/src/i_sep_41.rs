@@ -1,17 +1,100 @@
-use crate::errors::Sep0041Error;
-use soroban_sdk::{contractclient, Address, Env, String};
+use soroban_sdk::{contractclient, contracttype, Address, Bytes, BytesN, Env, String, Vec};
 
-#[contractclient(name = "Sep0041Client")]
-pub trait ISep0041 {
-    fn balance(env: &Env, id: Address) -> i128;
-    fn name(env: &Env) -> String;
-    fn decimals(env: &Env) -> u32;
-    fn symbol(env: &Env) -> String;
-    fn mint(env: &Env, to: Address, amount: i128) -> Result<bool, Sep0041Error>;
-    fn allowance(env: &Env, from: Address, spender: Address) -> i128;
-    fn approve(env: &Env, from: Address, spender: Address, amount: i128, live_until_ledger: u32);
-    fn transfer(env: &Env, from: Address, to: Address, amount: i128);
-    fn burn(env: &Env, from: Address, amount: i128);
-    fn transfer_from(env: &Env, spender: Address, from: Address, to: Address, amount: i128);
-    fn burn_from(env: &Env, spender: Address, from: Address, amount: i128);
+// an allowance of this amount is unlimited, `transfer_from` and `burn_from` never
+// decrement or rewrite it. It still expires at its expiration ledger and can be revoked,
+// while `decrease_allowance` turns it into an ordinary allowance
+pub const UNLIMITED_ALLOWANCE: i128 = i128::MAX;
+
+// balances are stored as shares, each worth `index / REBASE_INDEX_ONE / SHARES_PER_TOKEN`
+// tokens. The index is a fixed point number with 9 decimals and starts out at one, a share is
+// a billionth of a token unit so amounts convert to shares and back exact to the unit. At an
+// index of one that leaves room for `i128::MAX / SHARES_PER_TOKEN` tokens in total
+pub const REBASE_INDEX_ONE: i128 = 1_000_000_000;
+pub const SHARES_PER_TOKEN: i128 = 1_000_000_000;
+
+// a page of the live allowances of an owner as (spender, amount, expiration ledger).
+// `unlisted` is set while approvals made past a full spender index may still be live, those
+// show up in no page
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AllowancesPage {
+    pub allowances: Vec<(Address, i128, u32)>,
+    pub unlisted: bool,
+}
+
+// the token surface itself is `soroban_sdk::token::TokenInterface`, this trait
+// holds the privileged operations in the style of `StellarAssetInterface`
+#[contractclient(name = "Sep0041AdminClient")]
+pub trait ISep0041Admin {
+    fn mint(env: &Env, to: Address, amount: i128);
+    fn admin(env: &Env) -> Address;
+    // the proposed admin has until `live_until_ledger` (inclusive) to accept
+    fn propose_admin(env: &Env, new_admin: Address, live_until_ledger: u32);
+    fn accept_admin(env: &Env);
+    // permanently removes the admin, nothing can be minted afterwards
+    fn renounce_admin(env: &Env);
+    // deauthorized accounts can neither send nor receive tokens
+    fn set_authorized(env: &Env, id: Address, authorize: bool);
+    fn authorized(env: &Env, id: Address) -> bool;
+    // burns from any account, authorized or not, without its consent
+    fn clawback(env: &Env, from: Address, amount: i128);
+    // the pauser can pause and unpause alongside the admin
+    fn set_pauser(env: &Env, pauser: Address);
+    // while paused every balance or allowance changing call fails, reads keep working
+    fn pause(env: &Env, caller: Address);
+    fn unpause(env: &Env, caller: Address);
+    fn paused(env: &Env) -> bool;
+    // the cap can only ever be lowered, and never below the current total supply
+    fn set_max_supply(env: &Env, max_supply: i128);
+    // decimals are fixed at construction, only the name and symbol can change
+    fn set_metadata(env: &Env, name: String, symbol: String);
+    // a minter can mint up to `quota` every `period_ledgers` ledgers, adding an
+    // existing minter replaces its quota and starts a fresh period
+    fn add_minter(env: &Env, minter: Address, quota: i128, period_ledgers: u32);
+    fn remove_minter(env: &Env, minter: Address);
+    fn is_minter(env: &Env, id: Address) -> bool;
+    fn minter_mint(env: &Env, minter: Address, to: Address, amount: i128);
+    // swaps the contract code, storage is left untouched until `migrate` runs
+    fn upgrade(env: &Env, new_wasm_hash: BytesN<32>);
+    fn schema_version(env: &Env) -> u32;
+    // brings storage written by older versions up to the current schema. Balances
+    // are moved per holder so large holder sets can be migrated over several calls
+    fn migrate(env: &Env, holders: Vec<Address>);
+    // moves the allowances of the given (owner, spender) pairs out of the schema version 0
+    // layout. Their deadline becomes the ledger expected to close by then, capped at the
+    // maximum ttl, and expired ones are removed without being carried over
+    fn migrate_allowances(env: &Env, allowances: Vec<(Address, Address)>);
+    // `transfer` and `transfer_from` route `fee_bps` of every transfer to `collector`,
+    // 0 turns the fee off
+    fn set_transfer_fee(env: &Env, fee_bps: u32, collector: Address);
+    fn transfer_fee(env: &Env) -> u32;
+    fn fee_collector(env: &Env) -> Option<Address>;
+    // transfers from or to an exempt account, or the collector, carry no fee
+    fn set_fee_exempt(env: &Env, id: Address, exempt: bool);
+    fn fee_exempt(env: &Env, id: Address) -> bool;
+    // mints tokens that `to` cannot spend before `unlock_ledger`
+    fn mint_locked(env: &Env, to: Address, amount: i128, unlock_ledger: u32);
+    // the rebase oracle can rebase alongside the admin
+    fn set_rebase_oracle(env: &Env, oracle: Address);
+    // sets what a share is worth, balances, the total supply and votes follow the index up
+    // or down while allowances keep their amounts.
+    //
+    // Every balance and the total supply are rounded to the unit on their own, so once the
+    // index is not one the balances can add up to `total_supply` give or take half a unit per
+    // holder. The sum of balances equals the total supply, as the transfer fee keeps it, only
+    // in shares: `total_shares` is exactly the sum of `shares_of`
+    fn rebase(env: &Env, caller: Address, index: i128);
+    fn rebase_index(env: &Env) -> i128;
+}
+
+// implemented by contracts that want to act on tokens sent with `transfer_and_call`. The
+// tokens are already credited when the hook runs and `amount` is what arrived, after any
+// transfer fee. Returning false or failing reverts the whole transfer.
+//
+// Anyone can call the hook, so a receiver must keep the address of the token it trusts and
+// call `require_auth` on it before booking anything. The token is still on the call stack
+// while the hook runs and contracts cannot be re-entered, so the hook cannot call back into it
+#[contractclient(name = "Sep0041ReceiverClient")]
+pub trait ISep0041Receiver {
+    fn on_sep41_received(env: &Env, from: Address, amount: i128, data: Bytes) -> bool;
 }
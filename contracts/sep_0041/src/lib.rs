@@ -5,6 +5,8 @@ mod test;
 pub use i_sep_41::*;
 pub mod contract_sep_41;
 // pub use contract_sep_41::*;
-mod errors;
+pub mod errors;
+pub use errors::Sep0041Error;
 mod events;
 mod storage;
+mod strkey;
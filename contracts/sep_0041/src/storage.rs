@@ -1,22 +1,177 @@
-use soroban_sdk::{contracttype, Address};
+use soroban_sdk::{contracttype, symbol_short, Address, BytesN, String, Symbol, Vec};
 
-pub static SECONDS_IN_TIME: u64 = 60;
+// ledgers close roughly every five seconds
+pub const LEDGER_SECONDS: u64 = 5;
+pub const DAY_IN_LEDGERS: u32 = 17280;
+
+// the instance holds the token metadata and admin, every call keeps it alive for a week
+pub const INSTANCE_BUMP_AMOUNT: u32 = 7 * DAY_IN_LEDGERS;
+pub const INSTANCE_LIFETIME_THRESHOLD: u32 = INSTANCE_BUMP_AMOUNT - DAY_IN_LEDGERS;
+
+// balances are persistent entries, touching one keeps it alive for a month
+pub const BALANCE_BUMP_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
+pub const BALANCE_LIFETIME_THRESHOLD: u32 = BALANCE_BUMP_AMOUNT - DAY_IN_LEDGERS;
+
+// same key and layout as `soroban_token_sdk::metadata::TokenMetadata` so explorers can read it
+pub const METADATA_KEY: Symbol = symbol_short!("METADATA");
+
+// bumped whenever the storage layout changes, `migrate` brings older layouts up to it
+pub const SCHEMA_VERSION: u32 = 2;
+
+pub const MAX_DECIMALS: u32 = 18;
+pub const MAX_NAME_LENGTH: u32 = 32;
+pub const MAX_SYMBOL_LENGTH: u32 = 12;
+
+// locks are kept in a single entry per account, expired ones are dropped by `release`
+pub const MAX_LOCKS: u32 = 20;
+
+// every owner keeps an index of the spenders it approved, expired and revoked ones are
+// pruned to make room once it is full. Spenders approved past that are left out of it, the
+// owner keeps the last ledger such an approval can be live until
+pub const MAX_SPENDERS: u32 = 20;
+
+// the newest checkpoints of a history share an entry with its page count, so recording one
+// writes a single entry. Once that entry holds a full page it is moved out to a page entry
+pub const CHECKPOINT_PAGE_SIZE: u32 = 16;
+
+// transfer fees are in basis points of the amount, at most 10%
+pub const BPS_DENOMINATOR: i128 = 10_000;
+pub const MAX_FEE_BPS: u32 = 1_000;
+
+// every leg writes the recipient's balance and checkpoints. Whether a batch fits the
+// network's footprint limits, with fees and delegation touching more, is left to simulation
+pub const MAX_BATCH_SIZE: u32 = 23;
 
 #[contracttype]
-#[derive(Debug)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TokenMetadata {
+    pub decimal: u32,
+    pub name: String,
+    pub symbol: String,
+}
+
+#[contracttype]
+#[derive(Debug, Eq, PartialEq)]
 pub struct AllowanaceDetails {
     pub amount: i128,
-    pub deadline: u64,
+    pub expiration_ledger: u32,
+}
+
+#[contracttype]
+#[derive(Debug)]
+pub struct PendingAdminDetails {
+    pub admin: Address,
+    pub live_until_ledger: u32,
+}
+
+#[contracttype]
+#[derive(Debug)]
+pub struct MinterDetails {
+    pub quota: i128,
+    pub period_ledgers: u32,
+    pub period_start: u32,
+    pub minted_in_period: i128,
+}
+
+// the value a balance, the total supply, votes or the rebase index held at the end of
+// `ledger`. Balances, supply and votes are recorded in shares
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Checkpoint {
+    pub ledger: u32,
+    pub amount: i128,
+}
+
+// a history of checkpoints in ledger order, the `pages` full pages before `recent` are in
+// entries of their own
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Checkpoints {
+    pub pages: u32,
+    pub recent: Vec<Checkpoint>,
+}
+
+// `amount` of the balance cannot be spent before `unlock_ledger`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Lock {
+    pub amount: i128,
+    pub unlock_ledger: u32,
+}
+
+// what an owner signs to approve a spender through `permit`, the network id and
+// contract address keep a signature from being replayed on another network or token. It is
+// signed by the raw ed25519 key behind the account, whatever signers the account has set up
+#[contracttype]
+#[derive(Debug)]
+pub struct PermitPayload {
+    pub domain: Symbol,
+    pub network_id: BytesN<32>,
+    pub contract: Address,
+    pub owner: BytesN<32>,
+    pub spender: Address,
+    pub amount: i128,
+    pub expiration_ledger: u32,
+    pub nonce: u64,
 }
 
 #[contracttype]
 #[derive(Debug)]
 pub enum DataKey {
+    Balance(Address),
+    Admin,
+    TotalSupply,
+    Allowance(Address, Address),
+    PendingAdmin,
+    AuthRequired,
+    Authorized(Address),
+    Pauser,
+    Paused,
+    MaxSupply,
+    Minter(Address),
+    SchemaVersion,
+    PermitNonce(Address),
+    PermitEnabled(Address),
+    // checkpoint pages are append only, numbered from 0 in ledger order
+    BalanceCheckpoints(Address),
+    BalanceCheckpointPage(Address, u32),
+    SupplyCheckpoints,
+    SupplyCheckpointPage(u32),
+    Delegate(Address),
+    VotesCheckpoints(Address),
+    VotesCheckpointPage(Address, u32),
+    TransferFeeBps,
+    FeeCollector,
+    FeeExempt(Address),
+    Locks(Address),
+    Spenders(Address),
+    UnlistedUntil(Address),
+    Underlying,
+    RebaseIndex,
+    RebaseOracle,
+    IndexCheckpoints,
+    IndexCheckpointPage(u32),
+    // balances and supply in shares, `Balance` and `TotalSupply` hold whole tokens of schema
+    // version 1 and are dropped once the account or the supply first changes
+    Shares(Address),
+    TotalShares,
+}
+
+// keys of the original (schema version 0) layout, where everything lived in instance storage
+#[contracttype]
+#[derive(Debug)]
+pub enum LegacyDataKey {
     Balance(Address),
     Name,
     Symbol,
     Decimal,
-    Admin,
-    TotalSupply,
     Allowance(Address, Address),
 }
+
+// allowances of the schema version 0 layout, spendable until the unix timestamp `deadline`
+#[contracttype]
+#[derive(Debug)]
+pub struct LegacyAllowanceDetails {
+    pub amount: i128,
+    pub deadline: u64,
+}
@@ -0,0 +1,47 @@
+use soroban_sdk::{Address, Bytes, BytesN, Env};
+
+// version byte of a `G...` account strkey (ed25519 public key)
+const ACCOUNT_VERSION_BYTE: u8 = 6 << 3;
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+// builds the stellar account address that owns the given ed25519 public key
+pub fn account_address(env: &Env, public_key: &BytesN<32>) -> Address {
+    // version byte, public key and a little endian crc16 checksum
+    let mut raw = [0_u8; 35];
+    raw[0] = ACCOUNT_VERSION_BYTE;
+    raw[1..33].copy_from_slice(&public_key.to_array());
+    let checksum: u16 = crc16_xmodem(&raw[..33]);
+    raw[33..].copy_from_slice(&checksum.to_le_bytes());
+
+    // 35 bytes are exactly 56 base32 characters, so no padding is needed
+    let mut encoded = [0_u8; 56];
+    let mut buffer: u32 = 0;
+    let mut bits: u32 = 0;
+    let mut index: usize = 0;
+    for byte in raw {
+        buffer = (buffer << 8) | byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            encoded[index] = BASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize];
+            index += 1;
+        }
+    }
+
+    Address::from_string_bytes(&Bytes::from_array(env, &encoded))
+}
+
+fn crc16_xmodem(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0;
+    for byte in data {
+        crc ^= (*byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
@@ -1,9 +1,18 @@
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod test {
 
     use soroban_sdk::{
-        testutils::{Address as _, Ledger},
-        Address, Env, String,
+        symbol_short,
+        testutils::{
+            storage::{Instance as _, Persistent as _, Temporary as _},
+            Address as _, AuthorizedFunction, AuthorizedInvocation, Events, Ledger, MockAuth,
+            MockAuthInvoke,
+        },
+        token::{StellarAssetClient, TokenClient},
+        vec,
+        xdr::ToXdr,
+        Address, BytesN, Env, IntoVal, String, Symbol, Vec,
     };
 
     fn generate_addresses(env: &Env) -> (Address, Address, Address) {
@@ -15,26 +24,57 @@ mod test {
     }
 
     use crate::contract_sep_41::{Sep0041, Sep0041Client};
-    fn setup() -> (Env, Sep0041Client<'static>, Address) {
+
+    // the original instance storage layout, from before `upgrade` existed
+    mod sep_0041_v0 {
+        soroban_sdk::contractimport!(file = "test_wasms/sep_0041_v0.wasm");
+    }
+    // the first build with `upgrade` and `migrate`
+    mod sep_0041_v1 {
+        soroban_sdk::contractimport!(file = "test_wasms/sep_0041_v1.wasm");
+    }
+    use crate::errors::Sep0041Error;
+    use crate::i_sep_41::{
+        Sep0041AdminClient, REBASE_INDEX_ONE, SHARES_PER_TOKEN, UNLIMITED_ALLOWANCE,
+    };
+    use crate::storage::{
+        AllowanaceDetails, Checkpoints, DataKey, LegacyAllowanceDetails, LegacyDataKey, Lock,
+        PermitPayload, TokenMetadata, BALANCE_BUMP_AMOUNT, DAY_IN_LEDGERS,
+        INSTANCE_LIFETIME_THRESHOLD, MAX_BATCH_SIZE, MAX_FEE_BPS, MAX_LOCKS, MAX_SPENDERS,
+        METADATA_KEY, SCHEMA_VERSION,
+    };
+    use ed25519_dalek::{Signer, SigningKey};
+    fn setup() -> (Env, Sep0041Client<'static>, Address, Address) {
+        setup_with_config(false, None)
+    }
+
+    fn setup_with_config(
+        auth_required: bool,
+        max_supply: Option<i128>,
+    ) -> (Env, Sep0041Client<'static>, Address, Address) {
         let env: Env = Env::default();
 
         let (admin, _, _) = generate_addresses(&env);
         let contract_id = env.register(
             Sep0041,
             (
-                admin,
+                admin.clone(),
+                18_u32,
                 String::from_str(&env, "loaded"),
                 String::from_str(&env, "lsd"),
+                auth_required,
+                max_supply,
+                None::<Address>,
             ),
         );
         let contract_client = Sep0041Client::new(&env, &contract_id);
         env.mock_all_auths();
-        (env, contract_client, contract_id)
+        (env, contract_client, contract_id, admin)
     }
 
     #[test]
     fn test_constructor_values() {
-        let (env, contract, _) = setup();
+        let (env, contract, _, _) = setup();
         assert_eq!(contract.name(), String::from_str(&env, "loaded"));
         assert_eq!(contract.symbol(), String::from_str(&env, "lsd"));
         assert_eq!(contract.decimals(), 18_u32);
@@ -42,19 +82,18 @@ mod test {
 
     #[test]
     fn test_mint_and_balance() {
-        let (env, contract_instance, _) = setup();
+        let (env, contract_instance, _, _) = setup();
         let (_, user1, _) = generate_addresses(&env);
         assert_eq!(contract_instance.balance(&user1), 0);
-        let res = contract_instance.mint(&user1, &500);
+        contract_instance.mint(&user1, &500);
 
-        assert_eq!(res, true);
         assert_eq!(contract_instance.balance(&user1), 500);
         assert_eq!(contract_instance.total_supply(), 500);
     }
 
     #[test]
     fn test_transfer() {
-        let (env, contract_instance, _) = setup();
+        let (env, contract_instance, _, _) = setup();
         let (_, user1, user2) = generate_addresses(&env);
 
         contract_instance.mint(&user1, &300);
@@ -66,7 +105,7 @@ mod test {
 
     #[test]
     fn test_approve_and_allowance() {
-        let (env, contract_instance, _) = setup();
+        let (env, contract_instance, _, _) = setup();
         let (_, owner, spender) = generate_addresses(&env);
 
         contract_instance.mint(&owner, &400);
@@ -78,7 +117,7 @@ mod test {
 
     #[test]
     fn test_transfer_from_updates_allowance() {
-        let (env, contract_instance, _) = setup();
+        let (env, contract_instance, _, _) = setup();
         let (_, owner, spender) = generate_addresses(&env);
         let (_, _, recipient) = generate_addresses(&env);
 
@@ -93,7 +132,7 @@ mod test {
 
     #[test]
     fn test_burn_and_burn_from() {
-        let (env, contract_instance, _) = setup();
+        let (env, contract_instance, _, _) = setup();
         let (_, owner, spender) = generate_addresses(&env);
 
         // Burn
@@ -112,22 +151,22 @@ mod test {
     #[test]
     #[should_panic]
     fn test_transfer_insufficient_balance_fails() {
-        let (env, contract_instance, _) = setup();
+        let (env, contract_instance, _, _) = setup();
         let (_, user1, user2) = generate_addresses(&env);
         contract_instance.transfer(&user1, &user2, &50);
     }
 
     #[test]
-    #[should_panic(expected = "invalid amount")]
+    #[should_panic(expected = "Error(Contract, #4)")]
     fn test_transfer_zero_amount_fails() {
-        let (env, contract_instance, _) = setup();
+        let (env, contract_instance, _, _) = setup();
         let (_, user1, user2) = generate_addresses(&env);
         contract_instance.transfer(&user1, &user2, &0);
     }
 
     #[test]
     fn test_multiple_transfers() {
-        let (env, contract_instance, _) = setup();
+        let (env, contract_instance, _, _) = setup();
         let (_, user1, user2) = generate_addresses(&env);
 
         contract_instance.mint(&user1, &500);
@@ -141,7 +180,7 @@ mod test {
     #[test]
     #[should_panic]
     fn test_transfer_from_exceeds_allowance_fails() {
-        let (env, contract_instance, _) = setup();
+        let (env, contract_instance, _, _) = setup();
         let (_, owner, spender) = generate_addresses(&env);
         let (_, _, recipient) = generate_addresses(&env);
 
@@ -153,17 +192,2949 @@ mod test {
     #[test]
     #[should_panic]
     fn test_allowance_expiry_fails() {
-        let (env, contract_instance, _) = setup();
+        let (env, contract_instance, _, _) = setup();
         let (_, owner, spender) = generate_addresses(&env);
         let (_, _, recipient) = generate_addresses(&env);
 
         contract_instance.mint(&owner, &300);
         contract_instance.approve(&owner, &spender, &100, &1);
 
-        // Simulate ledger time passing beyond deadline
-        env.ledger()
-            .set_timestamp(env.ledger().timestamp() + (60 * 3));
+        // Simulate ledgers closing beyond the expiration ledger
+        env.ledger().set_sequence_number(2);
 
         contract_instance.transfer_from(&spender, &owner, &recipient, &50);
     }
+
+    #[test]
+    fn test_allowance_is_zero_after_expiration_ledger() {
+        let (env, contract_instance, _, _) = setup();
+        let (_, owner, spender) = generate_addresses(&env);
+
+        env.ledger().set_sequence_number(10);
+        contract_instance.approve(&owner, &spender, &100, &20);
+
+        env.ledger().set_sequence_number(20);
+        assert_eq!(contract_instance.allowance(&owner, &spender), 100);
+
+        env.ledger().set_sequence_number(21);
+        assert_eq!(contract_instance.allowance(&owner, &spender), 0);
+    }
+
+    #[test]
+    fn test_transfer_from_on_expiration_ledger() {
+        let (env, contract_instance, _, _) = setup();
+        let (_, owner, spender) = generate_addresses(&env);
+        let (_, _, recipient) = generate_addresses(&env);
+
+        contract_instance.mint(&owner, &300);
+        contract_instance.approve(&owner, &spender, &100, &5);
+
+        env.ledger().set_sequence_number(5);
+        contract_instance.transfer_from(&spender, &owner, &recipient, &60);
+
+        assert_eq!(contract_instance.balance(&recipient), 60);
+        assert_eq!(contract_instance.allowance(&owner, &spender), 40);
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #8)")]
+    fn test_approve_with_past_expiration_fails() {
+        let (env, contract_instance, _, _) = setup();
+        let (_, owner, spender) = generate_addresses(&env);
+
+        env.ledger().set_sequence_number(100);
+        contract_instance.approve(&owner, &spender, &100, &99);
+    }
+
+    #[test]
+    fn test_approve_beyond_max_ttl_fails() {
+        let (env, contract_instance, _, _) = setup();
+        let (_, owner, spender) = generate_addresses(&env);
+        env.ledger().set_sequence_number(100);
+        let max_expiration_ledger: u32 = 100 + env.ledger().get().max_entry_ttl - 1;
+
+        contract_instance.approve(&owner, &spender, &100, &max_expiration_ledger);
+        assert_eq!(contract_instance.allowance(&owner, &spender), 100);
+        for expiration_ledger in [max_expiration_ledger + 1, u32::MAX] {
+            let res = contract_instance.try_approve(&owner, &spender, &100, &expiration_ledger);
+            assert_eq!(res, Err(Ok(Sep0041Error::InvalidExpirationLedger.into())));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_burn_from_after_expiration_fails() {
+        let (env, contract_instance, _, _) = setup();
+        let (_, owner, spender) = generate_addresses(&env);
+
+        contract_instance.mint(&owner, &300);
+        contract_instance.approve(&owner, &spender, &100, &5);
+
+        env.ledger().set_sequence_number(6);
+        contract_instance.burn_from(&spender, &owner, &50);
+    }
+
+    #[test]
+    fn test_mint_emits_event() {
+        let (env, contract_instance, contract_id, admin) = setup();
+        let (_, user1, _) = generate_addresses(&env);
+
+        contract_instance.mint(&user1, &500);
+
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("mint"), admin, user1).into_val(&env),
+                    500_i128.into_val(&env),
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_transfer_emits_event() {
+        let (env, contract_instance, contract_id, _) = setup();
+        let (_, user1, user2) = generate_addresses(&env);
+
+        contract_instance.mint(&user1, &300);
+        contract_instance.transfer(&user1, &user2, &100);
+
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("transfer"), user1, user2).into_val(&env),
+                    100_i128.into_val(&env),
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_approve_emits_event() {
+        let (env, contract_instance, contract_id, _) = setup();
+        let (_, owner, spender) = generate_addresses(&env);
+
+        contract_instance.approve(&owner, &spender, &150, &5);
+
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("approve"), owner, spender).into_val(&env),
+                    (150_i128, 5_u32).into_val(&env),
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_transfer_from_emits_event() {
+        let (env, contract_instance, contract_id, _) = setup();
+        let (_, owner, spender) = generate_addresses(&env);
+        let (_, _, recipient) = generate_addresses(&env);
+
+        contract_instance.mint(&owner, &500);
+        contract_instance.approve(&owner, &spender, &200, &5);
+        contract_instance.transfer_from(&spender, &owner, &recipient, &150);
+
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("transfer"), owner, recipient).into_val(&env),
+                    150_i128.into_val(&env),
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_burn_and_burn_from_emit_events() {
+        let (env, contract_instance, contract_id, _) = setup();
+        let (_, owner, spender) = generate_addresses(&env);
+
+        contract_instance.mint(&owner, &300);
+        contract_instance.burn(&owner, &100);
+
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("burn"), owner.clone()).into_val(&env),
+                    100_i128.into_val(&env),
+                ),
+            ]
+        );
+
+        contract_instance.approve(&owner, &spender, &150, &5);
+        contract_instance.burn_from(&spender, &owner, &50);
+
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("burn"), owner).into_val(&env),
+                    50_i128.into_val(&env),
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_balance_and_allowance_storage_ttl() {
+        let (env, contract_instance, contract_id, _) = setup();
+        let (_, owner, spender) = generate_addresses(&env);
+
+        env.ledger().set_sequence_number(100);
+        contract_instance.mint(&owner, &500);
+        contract_instance.approve(&owner, &spender, &200, &1_100);
+
+        env.as_contract(&contract_id, || {
+            assert!(env.storage().instance().get_ttl() >= INSTANCE_LIFETIME_THRESHOLD);
+            assert_eq!(
+                env.storage()
+                    .persistent()
+                    .get_ttl(&DataKey::Shares(owner.clone())),
+                BALANCE_BUMP_AMOUNT
+            );
+            assert_eq!(
+                env.storage()
+                    .temporary()
+                    .get_ttl(&DataKey::Allowance(owner.clone(), spender.clone())),
+                1_000
+            );
+            assert!(!env
+                .storage()
+                .instance()
+                .has(&DataKey::Shares(owner.clone())));
+        });
+    }
+
+    #[test]
+    fn test_touched_balance_outlives_archival_window() {
+        let (env, contract_instance, contract_id, _) = setup();
+        let (_, holder, _) = generate_addresses(&env);
+
+        contract_instance.mint(&holder, &500);
+
+        // read the balance every few days for well past a single bump window
+        let mut sequence: u32 = 0;
+        while sequence < 3 * BALANCE_BUMP_AMOUNT {
+            sequence += 2 * DAY_IN_LEDGERS;
+            env.ledger().set_sequence_number(sequence);
+            assert_eq!(contract_instance.balance(&holder), 500);
+        }
+
+        env.as_contract(&contract_id, || {
+            assert!(
+                env.storage()
+                    .persistent()
+                    .get_ttl(&DataKey::Shares(holder.clone()))
+                    > BALANCE_BUMP_AMOUNT - DAY_IN_LEDGERS
+            );
+        });
+    }
+
+    #[test]
+    fn test_expired_allowance_entry_reads_as_zero() {
+        let (env, contract_instance, _, _) = setup();
+        let (_, owner, spender) = generate_addresses(&env);
+
+        contract_instance.approve(&owner, &spender, &100, &50);
+
+        // the temporary entry is gone once its ttl runs out
+        env.ledger().set_sequence_number(10_000);
+        assert_eq!(contract_instance.allowance(&owner, &spender), 0);
+    }
+
+    #[test]
+    fn test_transfer_insufficient_balance_error() {
+        let (env, contract_instance, _, _) = setup();
+        let (_, user1, user2) = generate_addresses(&env);
+
+        contract_instance.mint(&user1, &40);
+        assert_eq!(
+            contract_instance.try_transfer(&user1, &user2, &50),
+            Err(Ok(Sep0041Error::InsufficientBalance.into()))
+        );
+        assert_eq!(
+            contract_instance.try_burn(&user1, &50),
+            Err(Ok(Sep0041Error::InsufficientBalance.into()))
+        );
+    }
+
+    #[test]
+    fn test_negative_amounts_rejected() {
+        let (env, contract_instance, _, _) = setup();
+        let (_, owner, spender) = generate_addresses(&env);
+
+        contract_instance.mint(&owner, &100);
+        contract_instance.approve(&owner, &spender, &100, &5);
+
+        assert_eq!(
+            contract_instance.try_burn(&owner, &-10),
+            Err(Ok(Sep0041Error::InvalidAmount.into()))
+        );
+        assert_eq!(
+            contract_instance.try_transfer_from(&spender, &owner, &spender, &-10),
+            Err(Ok(Sep0041Error::InvalidAmount.into()))
+        );
+        assert_eq!(
+            contract_instance.try_burn_from(&spender, &owner, &0),
+            Err(Ok(Sep0041Error::InvalidAmount.into()))
+        );
+        assert_eq!(
+            contract_instance.try_mint(&owner, &-1),
+            Err(Ok(Sep0041Error::InvalidAmount.into()))
+        );
+    }
+
+    #[test]
+    fn test_allowance_errors() {
+        let (env, contract_instance, _, _) = setup();
+        let (_, owner, spender) = generate_addresses(&env);
+        let (_, _, recipient) = generate_addresses(&env);
+
+        contract_instance.mint(&owner, &500);
+
+        // no allowance at all
+        assert_eq!(
+            contract_instance.try_transfer_from(&spender, &owner, &recipient, &10),
+            Err(Ok(Sep0041Error::InsufficientAllowance.into()))
+        );
+
+        contract_instance.approve(&owner, &spender, &100, &5);
+        assert_eq!(
+            contract_instance.try_burn_from(&spender, &owner, &101),
+            Err(Ok(Sep0041Error::InsufficientAllowance.into()))
+        );
+
+        env.ledger().set_sequence_number(6);
+        assert_eq!(
+            contract_instance.try_transfer_from(&spender, &owner, &recipient, &10),
+            Err(Ok(Sep0041Error::AllowanceExpired.into()))
+        );
+    }
+
+    #[test]
+    fn test_mint_overflow_error() {
+        let (env, contract_instance, _, _) = setup();
+        let (_, user1, user2) = generate_addresses(&env);
+
+        // every token is SHARES_PER_TOKEN shares, so that is as much as the supply can hold
+        contract_instance.mint(&user1, &(i128::MAX / SHARES_PER_TOKEN));
+        assert_eq!(
+            contract_instance.try_mint(&user2, &1),
+            Err(Ok(Sep0041Error::Overflow.into()))
+        );
+    }
+
+    #[test]
+    fn test_transfer_to_self_keeps_balance() {
+        let (env, contract_instance, _, _) = setup();
+        let (_, user1, _) = generate_addresses(&env);
+
+        contract_instance.mint(&user1, &100);
+        contract_instance.transfer(&user1, &user1, &60);
+
+        assert_eq!(contract_instance.balance(&user1), 100);
+        assert_eq!(contract_instance.total_supply(), 100);
+    }
+
+    // runs the same scenario against any contract exposing the standard token interface
+    fn exercise_token_interface(env: &Env, token: &TokenClient, holder: &Address) {
+        let (_, spender, recipient) = generate_addresses(env);
+
+        assert_eq!(token.balance(holder), 1_000);
+
+        token.transfer(holder, &recipient, &100);
+        token.approve(holder, &spender, &300, &100);
+        assert_eq!(token.allowance(holder, &spender), 300);
+
+        token.transfer_from(&spender, holder, &recipient, &200);
+        token.burn_from(&spender, holder, &50);
+        token.burn(&recipient, &25);
+
+        assert_eq!(token.balance(holder), 650);
+        assert_eq!(token.balance(&recipient), 275);
+        assert_eq!(token.allowance(holder, &spender), 50);
+    }
+
+    #[test]
+    fn test_token_client_conformance() {
+        let (env, _, contract_id, _) = setup();
+        let (_, holder, _) = generate_addresses(&env);
+
+        Sep0041AdminClient::new(&env, &contract_id).mint(&holder, &1_000);
+
+        let token = TokenClient::new(&env, &contract_id);
+        assert_eq!(token.name(), String::from_str(&env, "loaded"));
+        assert_eq!(token.symbol(), String::from_str(&env, "lsd"));
+        assert_eq!(token.decimals(), 18);
+        exercise_token_interface(&env, &token, &holder);
+
+        // the stellar asset contract behaves identically under the same calls
+        let (issuer, _, _) = generate_addresses(&env);
+        let sac = env.register_stellar_asset_contract_v2(issuer);
+        StellarAssetClient::new(&env, &sac.address()).mint(&holder, &1_000);
+        exercise_token_interface(&env, &TokenClient::new(&env, &sac.address()), &holder);
+    }
+
+    fn mock_auth(
+        env: &Env,
+        contract_id: &Address,
+        signer: &Address,
+        fn_name: &str,
+        args: soroban_sdk::Vec<soroban_sdk::Val>,
+    ) {
+        env.mock_auths(&[MockAuth {
+            address: signer,
+            invoke: &MockAuthInvoke {
+                contract: contract_id,
+                fn_name,
+                args,
+                sub_invokes: &[],
+            },
+        }]);
+    }
+
+    #[test]
+    fn test_two_step_admin_transfer() {
+        let (env, contract_instance, contract_id, admin) = setup();
+        let (_, new_admin, holder) = generate_addresses(&env);
+        assert_eq!(contract_instance.admin(), admin);
+
+        mock_auth(
+            &env,
+            &contract_id,
+            &admin,
+            "propose_admin",
+            (new_admin.clone(), 100_u32).into_val(&env),
+        );
+        contract_instance.propose_admin(&new_admin, &100);
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (Symbol::new(&env, "propose_admin"), admin.clone()).into_val(&env),
+                    (new_admin.clone(), 100_u32).into_val(&env),
+                ),
+            ]
+        );
+        // nothing changes until the proposal is accepted
+        assert_eq!(contract_instance.admin(), admin);
+
+        mock_auth(
+            &env,
+            &contract_id,
+            &new_admin,
+            "accept_admin",
+            ().into_val(&env),
+        );
+        contract_instance.accept_admin();
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("set_admin"), admin.clone()).into_val(&env),
+                    new_admin.into_val(&env),
+                ),
+            ]
+        );
+        assert_eq!(contract_instance.admin(), new_admin);
+
+        // the old admin can no longer mint, the new one can
+        mock_auth(
+            &env,
+            &contract_id,
+            &admin,
+            "mint",
+            (holder.clone(), 10_i128).into_val(&env),
+        );
+        assert!(contract_instance.try_mint(&holder, &10).is_err());
+
+        mock_auth(
+            &env,
+            &contract_id,
+            &new_admin,
+            "mint",
+            (holder.clone(), 10_i128).into_val(&env),
+        );
+        contract_instance.mint(&holder, &10);
+        assert_eq!(contract_instance.balance(&holder), 10);
+    }
+
+    #[test]
+    fn test_propose_admin_requires_admin_auth() {
+        let (env, contract_instance, contract_id, _) = setup();
+        let (_, new_admin, _) = generate_addresses(&env);
+
+        mock_auth(
+            &env,
+            &contract_id,
+            &new_admin,
+            "propose_admin",
+            (new_admin.clone(), 100_u32).into_val(&env),
+        );
+        assert!(contract_instance
+            .try_propose_admin(&new_admin, &100)
+            .is_err());
+    }
+
+    #[test]
+    fn test_accept_admin_requires_pending_admin_auth() {
+        let (env, contract_instance, contract_id, admin) = setup();
+        let (_, new_admin, intruder) = generate_addresses(&env);
+
+        assert_eq!(
+            contract_instance.try_accept_admin(),
+            Err(Ok(Sep0041Error::NoPendingAdmin.into()))
+        );
+
+        mock_auth(
+            &env,
+            &contract_id,
+            &admin,
+            "propose_admin",
+            (new_admin.clone(), 100_u32).into_val(&env),
+        );
+        contract_instance.propose_admin(&new_admin, &100);
+
+        mock_auth(
+            &env,
+            &contract_id,
+            &intruder,
+            "accept_admin",
+            ().into_val(&env),
+        );
+        assert!(contract_instance.try_accept_admin().is_err());
+        assert_eq!(contract_instance.admin(), admin);
+    }
+
+    #[test]
+    fn test_pending_admin_expires() {
+        let (env, contract_instance, contract_id, admin) = setup();
+        let (_, new_admin, _) = generate_addresses(&env);
+
+        mock_auth(
+            &env,
+            &contract_id,
+            &admin,
+            "propose_admin",
+            (new_admin.clone(), 10_u32).into_val(&env),
+        );
+        contract_instance.propose_admin(&new_admin, &10);
+
+        env.ledger().set_sequence_number(11);
+        mock_auth(
+            &env,
+            &contract_id,
+            &new_admin,
+            "accept_admin",
+            ().into_val(&env),
+        );
+        assert_eq!(
+            contract_instance.try_accept_admin(),
+            Err(Ok(Sep0041Error::PendingAdminExpired.into()))
+        );
+        assert_eq!(contract_instance.admin(), admin);
+    }
+
+    #[test]
+    fn test_renounce_admin() {
+        let (env, contract_instance, contract_id, admin) = setup();
+        let (_, new_admin, holder) = generate_addresses(&env);
+
+        mock_auth(
+            &env,
+            &contract_id,
+            &admin,
+            "propose_admin",
+            (new_admin.clone(), 100_u32).into_val(&env),
+        );
+        contract_instance.propose_admin(&new_admin, &100);
+
+        mock_auth(
+            &env,
+            &contract_id,
+            &admin,
+            "renounce_admin",
+            ().into_val(&env),
+        );
+        contract_instance.renounce_admin();
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (Symbol::new(&env, "renounce_admin"), admin).into_val(&env),
+                    ().into_val(&env),
+                ),
+            ]
+        );
+
+        assert_eq!(
+            contract_instance.try_admin(),
+            Err(Ok(Sep0041Error::NoAdmin.into()))
+        );
+        // renouncing also drops the outstanding proposal
+        assert_eq!(
+            contract_instance.try_accept_admin(),
+            Err(Ok(Sep0041Error::NoPendingAdmin.into()))
+        );
+        env.mock_all_auths();
+        assert_eq!(
+            contract_instance.try_mint(&holder, &10),
+            Err(Ok(Sep0041Error::NoAdmin.into()))
+        );
+    }
+
+    #[test]
+    fn test_deauthorized_account_cannot_send_or_receive() {
+        let (env, contract_instance, contract_id, admin) = setup();
+        let (_, user1, user2) = generate_addresses(&env);
+
+        contract_instance.mint(&user1, &300);
+        assert!(contract_instance.authorized(&user1));
+
+        contract_instance.set_authorized(&user1, &false);
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (Symbol::new(&env, "set_authorized"), admin, user1.clone()).into_val(&env),
+                    false.into_val(&env),
+                ),
+            ]
+        );
+        assert!(!contract_instance.authorized(&user1));
+
+        assert_eq!(
+            contract_instance.try_transfer(&user1, &user2, &100),
+            Err(Ok(Sep0041Error::Unauthorized.into()))
+        );
+        assert_eq!(
+            contract_instance.try_burn(&user1, &100),
+            Err(Ok(Sep0041Error::Unauthorized.into()))
+        );
+
+        contract_instance.mint(&user2, &100);
+        assert_eq!(
+            contract_instance.try_transfer(&user2, &user1, &50),
+            Err(Ok(Sep0041Error::Unauthorized.into()))
+        );
+        assert_eq!(
+            contract_instance.try_mint(&user1, &50),
+            Err(Ok(Sep0041Error::Unauthorized.into()))
+        );
+
+        contract_instance.set_authorized(&user1, &true);
+        contract_instance.transfer(&user1, &user2, &100);
+        assert_eq!(contract_instance.balance(&user2), 200);
+    }
+
+    #[test]
+    fn test_auth_required_by_default() {
+        let (env, contract_instance, _, _) = setup_with_config(true, None);
+        let (_, user1, _) = generate_addresses(&env);
+
+        assert!(!contract_instance.authorized(&user1));
+        assert_eq!(
+            contract_instance.try_mint(&user1, &100),
+            Err(Ok(Sep0041Error::Unauthorized.into()))
+        );
+
+        contract_instance.set_authorized(&user1, &true);
+        contract_instance.mint(&user1, &100);
+        assert_eq!(contract_instance.balance(&user1), 100);
+    }
+
+    #[test]
+    fn test_clawback() {
+        let (env, contract_instance, contract_id, admin) = setup();
+        let (_, user1, _) = generate_addresses(&env);
+
+        contract_instance.mint(&user1, &300);
+        contract_instance.set_authorized(&user1, &false);
+
+        // clawback works whether or not the holder is authorized
+        contract_instance.clawback(&user1, &120);
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("clawback"), admin.clone(), user1.clone()).into_val(&env),
+                    120_i128.into_val(&env),
+                ),
+            ]
+        );
+        assert_eq!(contract_instance.balance(&user1), 180);
+        assert_eq!(contract_instance.total_supply(), 180);
+
+        assert_eq!(
+            contract_instance.try_clawback(&user1, &500),
+            Err(Ok(Sep0041Error::InsufficientBalance.into()))
+        );
+
+        mock_auth(
+            &env,
+            &contract_id,
+            &user1,
+            "clawback",
+            (user1.clone(), 10_i128).into_val(&env),
+        );
+        assert!(contract_instance.try_clawback(&user1, &10).is_err());
+    }
+
+    #[test]
+    fn test_pause_blocks_balance_changes() {
+        let (env, contract_instance, contract_id, admin) = setup();
+        let (_, owner, spender) = generate_addresses(&env);
+
+        contract_instance.mint(&owner, &500);
+        contract_instance.approve(&owner, &spender, &200, &5);
+
+        assert!(!contract_instance.paused());
+        contract_instance.pause(&admin);
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("paused"), admin.clone()).into_val(&env),
+                    ().into_val(&env),
+                ),
+            ]
+        );
+        assert!(contract_instance.paused());
+
+        let paused = Err(Ok(Sep0041Error::Paused.into()));
+        assert_eq!(contract_instance.try_mint(&owner, &1), paused);
+        assert_eq!(contract_instance.try_transfer(&owner, &spender, &1), paused);
+        assert_eq!(
+            contract_instance.try_transfer_from(&spender, &owner, &spender, &1),
+            paused
+        );
+        assert_eq!(contract_instance.try_burn(&owner, &1), paused);
+        assert_eq!(
+            contract_instance.try_burn_from(&spender, &owner, &1),
+            paused
+        );
+        assert_eq!(
+            contract_instance.try_approve(&owner, &spender, &1, &5),
+            paused
+        );
+
+        // reads keep working
+        assert_eq!(contract_instance.balance(&owner), 500);
+        assert_eq!(contract_instance.allowance(&owner, &spender), 200);
+        assert_eq!(contract_instance.total_supply(), 500);
+
+        contract_instance.unpause(&admin);
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("unpaused"), admin).into_val(&env),
+                    ().into_val(&env),
+                ),
+            ]
+        );
+        contract_instance.transfer(&owner, &spender, &100);
+        assert_eq!(contract_instance.balance(&spender), 100);
+    }
+
+    #[test]
+    fn test_pauser_role() {
+        let (env, contract_instance, contract_id, _) = setup();
+        let (_, pauser, stranger) = generate_addresses(&env);
+
+        assert_eq!(
+            contract_instance.try_pause(&pauser),
+            Err(Ok(Sep0041Error::Unauthorized.into()))
+        );
+
+        contract_instance.set_pauser(&pauser);
+
+        mock_auth(
+            &env,
+            &contract_id,
+            &pauser,
+            "pause",
+            (pauser.clone(),).into_val(&env),
+        );
+        contract_instance.pause(&pauser);
+        assert!(contract_instance.paused());
+
+        // naming the pauser without its signature is not enough
+        mock_auth(
+            &env,
+            &contract_id,
+            &stranger,
+            "unpause",
+            (pauser.clone(),).into_val(&env),
+        );
+        assert!(contract_instance.try_unpause(&pauser).is_err());
+
+        mock_auth(
+            &env,
+            &contract_id,
+            &pauser,
+            "unpause",
+            (pauser.clone(),).into_val(&env),
+        );
+        contract_instance.unpause(&pauser);
+        assert!(!contract_instance.paused());
+    }
+
+    #[test]
+    fn test_mint_respects_max_supply() {
+        let (env, contract_instance, _, _) = setup_with_config(false, Some(1_000));
+        let (_, user1, user2) = generate_addresses(&env);
+
+        assert_eq!(contract_instance.max_supply(), Some(1_000));
+        contract_instance.mint(&user1, &600);
+        contract_instance.mint(&user2, &400);
+        assert_eq!(
+            contract_instance.try_mint(&user2, &1),
+            Err(Ok(Sep0041Error::MaxSupplyExceeded.into()))
+        );
+
+        // burning frees room under the cap again
+        contract_instance.burn(&user1, &100);
+        contract_instance.mint(&user2, &100);
+        assert_eq!(contract_instance.total_supply(), 1_000);
+    }
+
+    #[test]
+    fn test_max_supply_can_only_be_lowered() {
+        let (env, contract_instance, contract_id, admin) = setup_with_config(false, Some(1_000));
+        let (_, user1, _) = generate_addresses(&env);
+
+        contract_instance.mint(&user1, &500);
+
+        assert_eq!(
+            contract_instance.try_set_max_supply(&1_001),
+            Err(Ok(Sep0041Error::InvalidMaxSupply.into()))
+        );
+        assert_eq!(
+            contract_instance.try_set_max_supply(&499),
+            Err(Ok(Sep0041Error::InvalidMaxSupply.into()))
+        );
+
+        contract_instance.set_max_supply(&800);
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (Symbol::new(&env, "set_max_supply"), admin).into_val(&env),
+                    800_i128.into_val(&env),
+                ),
+            ]
+        );
+        assert_eq!(contract_instance.max_supply(), Some(800));
+        assert_eq!(
+            contract_instance.try_mint(&user1, &301),
+            Err(Ok(Sep0041Error::MaxSupplyExceeded.into()))
+        );
+    }
+
+    #[test]
+    fn test_uncapped_supply_can_be_capped() {
+        let (env, contract_instance, _, _) = setup();
+        let (_, user1, _) = generate_addresses(&env);
+
+        assert_eq!(contract_instance.max_supply(), None);
+        contract_instance.mint(&user1, &500);
+        contract_instance.set_max_supply(&500);
+        assert_eq!(
+            contract_instance.try_mint(&user1, &1),
+            Err(Ok(Sep0041Error::MaxSupplyExceeded.into()))
+        );
+    }
+
+    #[test]
+    fn test_balance_overflow_error() {
+        let (env, contract_instance, _, _) = setup();
+        let (_, user1, user2) = generate_addresses(&env);
+
+        let max_amount: i128 = i128::MAX / SHARES_PER_TOKEN;
+        contract_instance.mint(&user1, &(max_amount - 10));
+        assert_eq!(
+            contract_instance.try_mint(&user1, &11),
+            Err(Ok(Sep0041Error::Overflow.into()))
+        );
+        contract_instance.mint(&user2, &10);
+        assert_eq!(contract_instance.total_supply(), max_amount);
+    }
+
+    fn register_with_metadata(env: &Env, decimal: u32, name: &str, symbol: &str) -> Address {
+        let (admin, _, _) = generate_addresses(env);
+        env.register(
+            Sep0041,
+            (
+                admin,
+                decimal,
+                String::from_str(env, name),
+                String::from_str(env, symbol),
+                false,
+                None::<i128>,
+                None::<Address>,
+            ),
+        )
+    }
+
+    #[test]
+    fn test_configurable_decimals_and_metadata_layout() {
+        let env: Env = Env::default();
+        let contract_id = register_with_metadata(&env, 7, "Bridged USD Coin", "USDC");
+        let contract_instance = Sep0041Client::new(&env, &contract_id);
+
+        assert_eq!(contract_instance.decimals(), 7);
+        assert_eq!(
+            contract_instance.name(),
+            String::from_str(&env, "Bridged USD Coin")
+        );
+
+        // explorers read the same key the soroban-token-sdk writes
+        let metadata: TokenMetadata = env.as_contract(&contract_id, || {
+            env.storage().instance().get(&METADATA_KEY).unwrap()
+        });
+        assert_eq!(
+            metadata,
+            TokenMetadata {
+                decimal: 7,
+                name: String::from_str(&env, "Bridged USD Coin"),
+                symbol: String::from_str(&env, "USDC"),
+            }
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #15)")]
+    fn test_constructor_rejects_too_many_decimals() {
+        let env: Env = Env::default();
+        register_with_metadata(&env, 19, "loaded", "lsd");
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #16)")]
+    fn test_constructor_rejects_empty_name() {
+        let env: Env = Env::default();
+        register_with_metadata(&env, 7, "", "lsd");
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #17)")]
+    fn test_constructor_rejects_invalid_symbol() {
+        let env: Env = Env::default();
+        register_with_metadata(&env, 7, "loaded", "l$d");
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #17)")]
+    fn test_constructor_rejects_long_symbol() {
+        let env: Env = Env::default();
+        register_with_metadata(&env, 7, "loaded", "ABCDEFGHIJKLM");
+    }
+
+    #[test]
+    fn test_set_metadata() {
+        let (env, contract_instance, contract_id, admin) = setup();
+        let name = String::from_str(&env, "loaded v2");
+        let symbol = String::from_str(&env, "LSD2");
+
+        contract_instance.set_metadata(&name, &symbol);
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (Symbol::new(&env, "set_metadata"), admin).into_val(&env),
+                    (name.clone(), symbol.clone()).into_val(&env),
+                ),
+            ]
+        );
+        assert_eq!(contract_instance.name(), name);
+        assert_eq!(contract_instance.symbol(), symbol);
+        assert_eq!(contract_instance.decimals(), 18);
+
+        assert_eq!(
+            contract_instance.try_set_metadata(&String::from_str(&env, "loaded\n"), &symbol),
+            Err(Ok(Sep0041Error::InvalidName.into()))
+        );
+
+        let (_, stranger, _) = generate_addresses(&env);
+        mock_auth(
+            &env,
+            &contract_id,
+            &stranger,
+            "set_metadata",
+            (name.clone(), symbol.clone()).into_val(&env),
+        );
+        assert!(contract_instance.try_set_metadata(&name, &symbol).is_err());
+    }
+
+    #[test]
+    fn test_minter_quota_per_period() {
+        let (env, contract_instance, contract_id, _) = setup();
+        let (_, minter, employee) = generate_addresses(&env);
+
+        env.ledger().set_sequence_number(100);
+        contract_instance.add_minter(&minter, &1_000, &50);
+        assert!(contract_instance.is_minter(&minter));
+
+        mock_auth(
+            &env,
+            &contract_id,
+            &minter,
+            "minter_mint",
+            (minter.clone(), employee.clone(), 600_i128).into_val(&env),
+        );
+        contract_instance.minter_mint(&minter, &employee, &600);
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("mint"), minter.clone(), employee.clone()).into_val(&env),
+                    600_i128.into_val(&env),
+                ),
+            ]
+        );
+
+        env.mock_all_auths();
+        assert_eq!(
+            contract_instance.try_minter_mint(&minter, &employee, &401),
+            Err(Ok(Sep0041Error::MinterQuotaExceeded.into()))
+        );
+        contract_instance.minter_mint(&minter, &employee, &400);
+
+        // the quota is restored once the period rolls over
+        env.ledger().set_sequence_number(149);
+        assert_eq!(
+            contract_instance.try_minter_mint(&minter, &employee, &1),
+            Err(Ok(Sep0041Error::MinterQuotaExceeded.into()))
+        );
+        env.ledger().set_sequence_number(150);
+        contract_instance.minter_mint(&minter, &employee, &1_000);
+
+        assert_eq!(contract_instance.balance(&employee), 2_000);
+        assert_eq!(contract_instance.total_supply(), 2_000);
+    }
+
+    #[test]
+    fn test_minter_mint_requires_minter_auth() {
+        let (env, contract_instance, contract_id, _) = setup();
+        let (_, minter, stranger) = generate_addresses(&env);
+
+        contract_instance.add_minter(&minter, &1_000, &50);
+
+        mock_auth(
+            &env,
+            &contract_id,
+            &stranger,
+            "minter_mint",
+            (minter.clone(), stranger.clone(), 10_i128).into_val(&env),
+        );
+        assert!(contract_instance
+            .try_minter_mint(&minter, &stranger, &10)
+            .is_err());
+
+        env.mock_all_auths();
+        assert_eq!(
+            contract_instance.try_minter_mint(&stranger, &stranger, &10),
+            Err(Ok(Sep0041Error::NotMinter.into()))
+        );
+    }
+
+    #[test]
+    fn test_remove_minter() {
+        let (env, contract_instance, contract_id, admin) = setup();
+        let (_, minter, employee) = generate_addresses(&env);
+
+        assert_eq!(
+            contract_instance.try_add_minter(&minter, &0, &50),
+            Err(Ok(Sep0041Error::InvalidMinterConfig.into()))
+        );
+        contract_instance.add_minter(&minter, &1_000, &50);
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (
+                        Symbol::new(&env, "add_minter"),
+                        admin.clone(),
+                        minter.clone()
+                    )
+                        .into_val(&env),
+                    (1_000_i128, 50_u32).into_val(&env),
+                ),
+            ]
+        );
+
+        contract_instance.remove_minter(&minter);
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (Symbol::new(&env, "remove_minter"), admin, minter.clone()).into_val(&env),
+                    ().into_val(&env),
+                ),
+            ]
+        );
+        assert!(!contract_instance.is_minter(&minter));
+        assert_eq!(
+            contract_instance.try_minter_mint(&minter, &employee, &10),
+            Err(Ok(Sep0041Error::NotMinter.into()))
+        );
+    }
+
+    #[test]
+    fn test_upgrade_to_uploaded_wasm() {
+        let (env, contract_instance, contract_id, admin) = setup();
+        let (_, holder, _) = generate_addresses(&env);
+
+        let new_wasm_hash = env.deployer().upload_contract_wasm(sep_0041_v1::WASM);
+
+        mock_auth(
+            &env,
+            &contract_id,
+            &holder,
+            "upgrade",
+            (new_wasm_hash.clone(),).into_val(&env),
+        );
+        assert!(contract_instance.try_upgrade(&new_wasm_hash).is_err());
+
+        mock_auth(
+            &env,
+            &contract_id,
+            &admin,
+            "upgrade",
+            (new_wasm_hash.clone(),).into_val(&env),
+        );
+        contract_instance.upgrade(&new_wasm_hash);
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("upgrade"), admin.clone()).into_val(&env),
+                    new_wasm_hash.into_val(&env),
+                ),
+            ]
+        );
+
+        // the instance survives and the uploaded build now serves the calls. v1 predates
+        // shares, so balances are left out
+        env.mock_all_auths();
+        let upgraded = sep_0041_v1::Client::new(&env, &contract_id);
+        assert_eq!(upgraded.admin(), admin);
+        assert_eq!(upgraded.name(), String::from_str(&env, "loaded"));
+        assert_eq!(upgraded.decimals(), 18);
+        assert_eq!(upgraded.schema_version(), SCHEMA_VERSION);
+        upgraded.mint(&holder, &500);
+        assert_eq!(upgraded.balance(&holder), 500);
+    }
+
+    // a real v0 deployment migrated by the frozen v1 build, which checks the hand written
+    // layout of `register_legacy_layout` against what v0 actually stored
+    #[test]
+    fn test_migrate_legacy_instance_layout() {
+        let env: Env = Env::default();
+        env.mock_all_auths();
+        let (admin, holder, other_holder) = generate_addresses(&env);
+
+        let contract_id = env.register(
+            sep_0041_v0::WASM,
+            (
+                admin.clone(),
+                String::from_str(&env, "loaded"),
+                String::from_str(&env, "lsd"),
+            ),
+        );
+        let legacy = sep_0041_v0::Client::new(&env, &contract_id);
+        legacy.mint(&holder, &500);
+        legacy.mint(&other_holder, &300);
+
+        // legacy deployments predate `upgrade`, so swap the code from inside the contract
+        let new_wasm_hash = env.deployer().upload_contract_wasm(sep_0041_v1::WASM);
+        env.as_contract(&contract_id, || {
+            env.deployer().update_current_contract_wasm(new_wasm_hash);
+        });
+
+        let contract_instance = Sep0041Client::new(&env, &contract_id);
+        assert_eq!(contract_instance.schema_version(), 0);
+
+        // holders can be migrated over several calls
+        contract_instance.migrate(&vec![&env, holder.clone()]);
+        assert_eq!(contract_instance.schema_version(), 1);
+        assert_eq!(contract_instance.name(), String::from_str(&env, "loaded"));
+        assert_eq!(contract_instance.decimals(), 18);
+        assert_eq!(contract_instance.balance(&holder), 500);
+        assert_eq!(contract_instance.balance(&other_holder), 0);
+
+        contract_instance.migrate(&vec![&env, other_holder.clone(), holder.clone()]);
+        assert_eq!(contract_instance.balance(&other_holder), 300);
+        assert_eq!(contract_instance.balance(&holder), 500);
+        assert_eq!(contract_instance.total_supply(), 800);
+
+        env.as_contract(&contract_id, || {
+            assert!(!env
+                .storage()
+                .instance()
+                .has(&LegacyDataKey::Balance(holder.clone())));
+            assert!(env
+                .storage()
+                .persistent()
+                .has(&DataKey::Balance(holder.clone())));
+        });
+    }
+
+    // a native instance whose storage is rewritten to the schema version 0 layout, so
+    // `migrate` runs the current code rather than a frozen build
+    fn register_legacy_layout(env: &Env, admin: &Address) -> Address {
+        let contract_id = env.register(
+            Sep0041,
+            (
+                admin.clone(),
+                18_u32,
+                String::from_str(env, "loaded"),
+                String::from_str(env, "lsd"),
+                false,
+                None::<i128>,
+                None::<Address>,
+            ),
+        );
+        env.as_contract(&contract_id, || {
+            let instance = env.storage().instance();
+            instance.remove(&METADATA_KEY);
+            instance.remove(&DataKey::SchemaVersion);
+            instance.remove(&DataKey::AuthRequired);
+            instance.set(&LegacyDataKey::Name, &String::from_str(env, "loaded"));
+            instance.set(&LegacyDataKey::Symbol, &String::from_str(env, "lsd"));
+            instance.set(&LegacyDataKey::Decimal, &18_u32);
+        });
+        contract_id
+    }
+
+    #[test]
+    fn test_migrate_legacy_layout_natively() {
+        let env: Env = Env::default();
+        env.mock_all_auths();
+        let (admin, holder, other_holder) = generate_addresses(&env);
+        let spender: Address = Address::generate(&env);
+        env.ledger().set_sequence_number(100);
+        env.ledger().set_timestamp(10_000);
+
+        let contract_id = register_legacy_layout(&env, &admin);
+        env.as_contract(&contract_id, || {
+            let instance = env.storage().instance();
+            instance.set(&LegacyDataKey::Balance(holder.clone()), &500_i128);
+            instance.set(&LegacyDataKey::Balance(other_holder.clone()), &300_i128);
+            instance.set(&DataKey::TotalSupply, &800_i128);
+            let allowance = LegacyAllowanceDetails {
+                amount: 300,
+                deadline: 10_600,
+            };
+            instance.set(
+                &LegacyDataKey::Allowance(holder.clone(), spender.clone()),
+                &allowance,
+            );
+            let expired = LegacyAllowanceDetails {
+                amount: 100,
+                deadline: 9_999,
+            };
+            instance.set(
+                &LegacyDataKey::Allowance(holder.clone(), other_holder.clone()),
+                &expired,
+            );
+        });
+
+        let contract_instance = Sep0041Client::new(&env, &contract_id);
+        assert_eq!(contract_instance.schema_version(), 0);
+        // delegated before its balance is moved, the votes follow the migrated shares
+        contract_instance.delegate(&holder, &holder);
+
+        contract_instance.migrate(&vec![&env, holder.clone()]);
+        assert_eq!(contract_instance.schema_version(), SCHEMA_VERSION);
+        assert_eq!(contract_instance.name(), String::from_str(&env, "loaded"));
+        assert_eq!(contract_instance.balance(&holder), 500);
+        assert_eq!(contract_instance.shares_of(&holder), 500 * SHARES_PER_TOKEN);
+        assert_eq!(contract_instance.get_votes(&holder), 500);
+        contract_instance.migrate(&vec![&env, other_holder.clone(), holder.clone()]);
+        assert_eq!(contract_instance.balance(&other_holder), 300);
+        assert_eq!(contract_instance.balance(&holder), 500);
+        assert_eq!(contract_instance.total_shares(), 800 * SHARES_PER_TOKEN);
+
+        contract_instance.migrate_allowances(&vec![
+            &env,
+            (holder.clone(), spender.clone()),
+            (holder.clone(), other_holder.clone()),
+        ]);
+        // 600 seconds left, at five seconds a ledger
+        assert_eq!(
+            stored_allowance(&env, &contract_id, &holder, &spender),
+            Some(AllowanaceDetails {
+                amount: 300,
+                expiration_ledger: 220,
+            })
+        );
+        assert_eq!(contract_instance.allowance(&holder, &other_holder), 0);
+        env.as_contract(&contract_id, || {
+            let instance = env.storage().instance();
+            assert!(!instance.has(&LegacyDataKey::Balance(holder.clone())));
+            assert!(!instance.has(&LegacyDataKey::Allowance(holder.clone(), spender.clone())));
+            assert!(!instance.has(&LegacyDataKey::Allowance(
+                holder.clone(),
+                other_holder.clone()
+            )));
+        });
+
+        // migrated balances take part in checkpoints, votes and rebases
+        env.ledger().set_sequence_number(110);
+        contract_instance.rebase(&admin, &(REBASE_INDEX_ONE * 2));
+        env.ledger().set_sequence_number(120);
+        assert_eq!(contract_instance.balance(&holder), 1000);
+        assert_eq!(contract_instance.balance_at(&holder, &105), 500);
+        assert_eq!(contract_instance.get_past_votes(&holder, &105), 500);
+        assert_eq!(contract_instance.get_votes(&holder), 1000);
+        assert_eq!(contract_instance.total_supply(), 1600);
+
+        contract_instance.transfer_from(&spender, &holder, &spender, &300);
+        assert_eq!(contract_instance.balance(&spender), 300);
+        assert_eq!(contract_instance.allowance(&holder, &spender), 0);
+    }
+
+    fn sign_permit(
+        env: &Env,
+        contract_id: &Address,
+        signing_key: &SigningKey,
+        spender: &Address,
+        amount: i128,
+        expiration_ledger: u32,
+        nonce: u64,
+    ) -> BytesN<64> {
+        let payload = PermitPayload {
+            domain: Symbol::new(env, "sep41_permit"),
+            network_id: env.ledger().network_id(),
+            contract: contract_id.clone(),
+            owner: BytesN::from_array(env, &signing_key.verifying_key().to_bytes()),
+            spender: spender.clone(),
+            amount,
+            expiration_ledger,
+            nonce,
+        };
+        let digest = env.crypto().sha256(&payload.to_xdr(env));
+        BytesN::from_array(env, &signing_key.sign(&digest.to_array()).to_bytes())
+    }
+
+    fn permit_owner(env: &Env, signing_key: &SigningKey) -> (BytesN<32>, Address) {
+        let public_key = signing_key.verifying_key().to_bytes();
+        let owner = Address::from_str(
+            env,
+            &stellar_strkey::ed25519::PublicKey(public_key).to_string(),
+        );
+        (BytesN::from_array(env, &public_key), owner)
+    }
+
+    #[test]
+    fn test_permit_sets_allowance() {
+        let (env, contract_instance, contract_id, _) = setup();
+        let (_, spender, recipient) = generate_addresses(&env);
+        let signing_key = SigningKey::from_bytes(&[7; 32]);
+        let (owner_pubkey, owner) = permit_owner(&env, &signing_key);
+
+        contract_instance.mint(&owner, &500);
+        contract_instance.set_permit_enabled(&owner, &true);
+        assert_eq!(contract_instance.permit_nonce(&owner), 1);
+
+        let signature = sign_permit(&env, &contract_id, &signing_key, &spender, 200, 1000, 1);
+        contract_instance.permit(&owner_pubkey, &spender, &200, &1000, &1, &signature);
+
+        // no account authorization is needed, the signature stands in for it
+        assert!(env.auths().is_empty());
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("approve"), owner.clone(), spender.clone()).into_val(&env),
+                    (200_i128, 1000_u32).into_val(&env),
+                ),
+            ]
+        );
+        assert_eq!(contract_instance.allowance(&owner, &spender), 200);
+        assert_eq!(contract_instance.permit_nonce(&owner), 2);
+
+        contract_instance.transfer_from(&spender, &owner, &recipient, &150);
+        assert_eq!(contract_instance.balance(&recipient), 150);
+        assert_eq!(contract_instance.allowance(&owner, &spender), 50);
+    }
+
+    #[test]
+    fn test_permit_rejects_replayed_nonce() {
+        let (env, contract_instance, contract_id, _) = setup();
+        let (_, spender, _) = generate_addresses(&env);
+        let signing_key = SigningKey::from_bytes(&[7; 32]);
+        let (owner_pubkey, owner) = permit_owner(&env, &signing_key);
+        contract_instance.set_permit_enabled(&owner, &true);
+
+        let signature = sign_permit(&env, &contract_id, &signing_key, &spender, 200, 1000, 1);
+        contract_instance.permit(&owner_pubkey, &spender, &200, &1000, &1, &signature);
+        contract_instance.approve(&owner, &spender, &10, &1000);
+
+        let res =
+            contract_instance.try_permit(&owner_pubkey, &spender, &200, &1000, &1, &signature);
+        assert_eq!(res, Err(Ok(Sep0041Error::InvalidNonce.into())));
+        assert_eq!(contract_instance.allowance(&owner, &spender), 10);
+
+        // a nonce from the future is rejected as well
+        let signature = sign_permit(&env, &contract_id, &signing_key, &spender, 200, 1000, 5);
+        let res =
+            contract_instance.try_permit(&owner_pubkey, &spender, &200, &1000, &5, &signature);
+        assert_eq!(res, Err(Ok(Sep0041Error::InvalidNonce.into())));
+    }
+
+    #[test]
+    fn test_permits_are_opt_in() {
+        let (env, contract_instance, contract_id, _) = setup();
+        let (_, spender, _) = generate_addresses(&env);
+        let signing_key = SigningKey::from_bytes(&[7; 32]);
+        let (owner_pubkey, owner) = permit_owner(&env, &signing_key);
+        assert!(!contract_instance.permit_enabled(&owner));
+
+        // off until the account itself turns them on
+        let signature = sign_permit(&env, &contract_id, &signing_key, &spender, 200, 1000, 0);
+        let res =
+            contract_instance.try_permit(&owner_pubkey, &spender, &200, &1000, &0, &signature);
+        assert_eq!(res, Err(Ok(Sep0041Error::PermitNotEnabled.into())));
+
+        contract_instance.set_permit_enabled(&owner, &true);
+        assert_eq!(
+            env.auths()[0],
+            (
+                owner.clone(),
+                AuthorizedInvocation {
+                    function: AuthorizedFunction::Contract((
+                        contract_id.clone(),
+                        Symbol::new(&env, "set_permit_enabled"),
+                        (owner.clone(), true).into_val(&env),
+                    )),
+                    sub_invocations: Default::default(),
+                }
+            )
+        );
+        assert!(contract_instance.permit_enabled(&owner));
+        assert_eq!(contract_instance.permit_nonce(&owner), 1);
+
+        // what was signed before they were turned on is retired
+        let res =
+            contract_instance.try_permit(&owner_pubkey, &spender, &200, &1000, &0, &signature);
+        assert_eq!(res, Err(Ok(Sep0041Error::InvalidNonce.into())));
+
+        // turning them back off retires what was signed in between
+        let signature = sign_permit(&env, &contract_id, &signing_key, &spender, 200, 1000, 1);
+        contract_instance.set_permit_enabled(&owner, &false);
+        assert!(!contract_instance.permit_enabled(&owner));
+        let res =
+            contract_instance.try_permit(&owner_pubkey, &spender, &200, &1000, &1, &signature);
+        assert_eq!(res, Err(Ok(Sep0041Error::PermitNotEnabled.into())));
+        contract_instance.set_permit_enabled(&owner, &true);
+        let res =
+            contract_instance.try_permit(&owner_pubkey, &spender, &200, &1000, &1, &signature);
+        assert_eq!(res, Err(Ok(Sep0041Error::InvalidNonce.into())));
+
+        let signature = sign_permit(&env, &contract_id, &signing_key, &spender, 200, 1000, 3);
+        contract_instance.permit(&owner_pubkey, &spender, &200, &1000, &3, &signature);
+        assert_eq!(contract_instance.allowance(&owner, &spender), 200);
+    }
+
+    #[test]
+    fn test_permit_rejects_bad_signature() {
+        let (env, contract_instance, contract_id, _) = setup();
+        let (_, spender, other_spender) = generate_addresses(&env);
+        let signing_key = SigningKey::from_bytes(&[7; 32]);
+        let (owner_pubkey, owner) = permit_owner(&env, &signing_key);
+        contract_instance.set_permit_enabled(&owner, &true);
+
+        // signed for a different spender
+        let signature = sign_permit(
+            &env,
+            &contract_id,
+            &signing_key,
+            &other_spender,
+            200,
+            1000,
+            1,
+        );
+        let res =
+            contract_instance.try_permit(&owner_pubkey, &spender, &200, &1000, &1, &signature);
+        assert!(res.is_err());
+
+        // signed by a different key
+        let other_key = SigningKey::from_bytes(&[8; 32]);
+        let signature = sign_permit(&env, &contract_id, &other_key, &spender, 200, 1000, 1);
+        let res =
+            contract_instance.try_permit(&owner_pubkey, &spender, &200, &1000, &1, &signature);
+        assert!(res.is_err());
+
+        // signed for another deployment of the token
+        let other_contract_id = Address::generate(&env);
+        let signature = sign_permit(
+            &env,
+            &other_contract_id,
+            &signing_key,
+            &spender,
+            200,
+            1000,
+            1,
+        );
+        let res =
+            contract_instance.try_permit(&owner_pubkey, &spender, &200, &1000, &1, &signature);
+        assert!(res.is_err());
+
+        assert_eq!(contract_instance.allowance(&owner, &spender), 0);
+        assert_eq!(contract_instance.permit_nonce(&owner), 1);
+    }
+
+    fn batch_of(env: &Env, size: u32, amount: i128) -> Vec<(Address, i128)> {
+        let mut recipients: Vec<(Address, i128)> = Vec::new(env);
+        for _ in 0..size {
+            recipients.push_back((Address::generate(env), amount));
+        }
+        recipients
+    }
+
+    #[test]
+    fn test_batch_transfer() {
+        let (env, contract_instance, contract_id, _) = setup();
+        let (_, from, _) = generate_addresses(&env);
+        let (_, first, second) = generate_addresses(&env);
+
+        contract_instance.mint(&from, &1000);
+        let recipients = vec![&env, (first.clone(), 100_i128), (second.clone(), 250_i128)];
+        contract_instance.batch_transfer(&from, &recipients);
+
+        // a single authorization covers every leg
+        let auths = env.auths();
+        assert_eq!(auths.len(), 1);
+        assert_eq!(
+            auths[0],
+            (
+                from.clone(),
+                AuthorizedInvocation {
+                    function: AuthorizedFunction::Contract((
+                        contract_id.clone(),
+                        Symbol::new(&env, "batch_transfer"),
+                        (from.clone(), recipients.clone()).into_val(&env),
+                    )),
+                    sub_invocations: Default::default(),
+                },
+            )
+        );
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("transfer"), from.clone(), first.clone()).into_val(&env),
+                    100_i128.into_val(&env),
+                ),
+                (
+                    contract_id.clone(),
+                    (symbol_short!("transfer"), from.clone(), second.clone()).into_val(&env),
+                    250_i128.into_val(&env),
+                ),
+            ]
+        );
+        assert_eq!(contract_instance.balance(&from), 650);
+        assert_eq!(contract_instance.balance(&first), 100);
+        assert_eq!(contract_instance.balance(&second), 250);
+        assert_eq!(contract_instance.total_supply(), 1000);
+    }
+
+    #[test]
+    fn test_batch_transfer_rejects_invalid_batches() {
+        let (env, contract_instance, _, _) = setup();
+        let (_, from, recipient) = generate_addresses(&env);
+        contract_instance.mint(&from, &1000);
+
+        let res = contract_instance.try_batch_transfer(&from, &Vec::new(&env));
+        assert_eq!(res, Err(Ok(Sep0041Error::EmptyBatch.into())));
+
+        let duplicated = vec![
+            &env,
+            (recipient.clone(), 10_i128),
+            (recipient.clone(), 20_i128),
+        ];
+        let res = contract_instance.try_batch_transfer(&from, &duplicated);
+        assert_eq!(res, Err(Ok(Sep0041Error::DuplicateRecipient.into())));
+
+        let oversized = batch_of(&env, MAX_BATCH_SIZE + 1, 1);
+        let res = contract_instance.try_batch_transfer(&from, &oversized);
+        assert_eq!(res, Err(Ok(Sep0041Error::BatchTooLarge.into())));
+
+        let zero_leg = vec![
+            &env,
+            (recipient.clone(), 10_i128),
+            (Address::generate(&env), 0_i128),
+        ];
+        let res = contract_instance.try_batch_transfer(&from, &zero_leg);
+        assert_eq!(res, Err(Ok(Sep0041Error::InvalidAmount.into())));
+
+        assert_eq!(contract_instance.balance(&from), 1000);
+        assert_eq!(contract_instance.balance(&recipient), 0);
+    }
+
+    #[test]
+    fn test_batch_transfer_is_atomic() {
+        let (env, contract_instance, _, _) = setup();
+        let (_, from, recipient) = generate_addresses(&env);
+        contract_instance.mint(&from, &500);
+
+        // each leg is affordable on its own but the total is not
+        let recipients = vec![
+            &env,
+            (recipient.clone(), 300_i128),
+            (Address::generate(&env), 300_i128),
+        ];
+        let res = contract_instance.try_batch_transfer(&from, &recipients);
+        assert_eq!(res, Err(Ok(Sep0041Error::InsufficientBalance.into())));
+        assert_eq!(contract_instance.balance(&from), 500);
+        assert_eq!(contract_instance.balance(&recipient), 0);
+
+        let overflowing = vec![
+            &env,
+            (recipient.clone(), i128::MAX),
+            (Address::generate(&env), 1_i128),
+        ];
+        let res = contract_instance.try_batch_transfer(&from, &overflowing);
+        assert_eq!(res, Err(Ok(Sep0041Error::Overflow.into())));
+    }
+
+    #[test]
+    fn test_batch_transfer_to_deauthorized_recipient_reverts() {
+        let (env, contract_instance, _, _) = setup();
+        let (_, from, recipient) = generate_addresses(&env);
+        let blocked: Address = Address::generate(&env);
+        contract_instance.mint(&from, &500);
+        contract_instance.set_authorized(&blocked, &false);
+
+        let recipients = vec![&env, (recipient.clone(), 100_i128), (blocked, 100_i128)];
+        let res = contract_instance.try_batch_transfer(&from, &recipients);
+        assert_eq!(res, Err(Ok(Sep0041Error::Unauthorized.into())));
+        assert_eq!(contract_instance.balance(&from), 500);
+        assert_eq!(contract_instance.balance(&recipient), 0);
+    }
+
+    #[test]
+    fn test_batch_transfer_largest_batch_fits_default_limits() {
+        let (env, contract_instance, _, _) = setup();
+        let (_, from, _) = generate_addresses(&env);
+        contract_instance.mint(&from, &1_000_000);
+        let recipients = batch_of(&env, MAX_BATCH_SIZE, 10);
+        for (to, _) in recipients.iter() {
+            contract_instance.mint(&to, &1);
+        }
+
+        // a later ledger, so every balance gets a new checkpoint
+        env.ledger()
+            .set_sequence_number(env.ledger().sequence() + 1);
+        env.cost_estimate().budget().reset_default();
+        contract_instance.batch_transfer(&from, &recipients);
+
+        // the default budget traps on overrun, so getting here means the call fit. The
+        // contract is registered natively, vm costs are not part of these figures
+        let resources = env.cost_estimate().resources();
+        assert!(resources.instructions < 100_000_000);
+        assert!(resources.mem_bytes < 40 * 1024 * 1024);
+        // the balance and checkpoints of the sender and every recipient, and the auth nonce
+        assert_eq!(resources.write_entries, 2 * MAX_BATCH_SIZE + 3);
+        assert_eq!(
+            contract_instance.balance(&from),
+            1_000_000 - 10 * MAX_BATCH_SIZE as i128
+        );
+    }
+
+    #[test]
+    fn test_checkpoints_across_many_ledgers() {
+        let (env, contract_instance, contract_id, _) = setup();
+        let (_, holder, other) = generate_addresses(&env);
+        let start: u32 = 1000;
+
+        // balances change every third ledger, the ledgers in between have no checkpoint. The
+        // histories run over several full pages
+        let mut holder_balances: [i128; 120] = [0; 120];
+        let mut supplies: [i128; 120] = [0; 120];
+        for step in 0..120_u32 {
+            env.ledger().set_sequence_number(start + step);
+            match step % 6 {
+                0 => contract_instance.mint(&holder, &(100 + step as i128)),
+                3 => contract_instance.transfer(&holder, &other, &(10 + step as i128)),
+                _ => (),
+            }
+            if step % 12 == 9 {
+                contract_instance.burn(&other, &5);
+            }
+            holder_balances[step as usize] = contract_instance.balance(&holder);
+            supplies[step as usize] = contract_instance.total_supply();
+        }
+        env.ledger().set_sequence_number(start + 120);
+        env.as_contract(&contract_id, || {
+            let checkpoints: Checkpoints = env
+                .storage()
+                .persistent()
+                .get(&DataKey::BalanceCheckpoints(holder.clone()))
+                .unwrap();
+            assert_eq!(checkpoints.pages, 2);
+        });
+
+        for step in 0..120_u32 {
+            let ledger: u32 = start + step;
+            assert_eq!(
+                contract_instance.balance_at(&holder, &ledger),
+                holder_balances[step as usize]
+            );
+            assert_eq!(
+                contract_instance.total_supply_at(&ledger),
+                supplies[step as usize]
+            );
+        }
+
+        // nothing before the first mint
+        assert_eq!(contract_instance.balance_at(&holder, &(start - 1)), 0);
+        assert_eq!(contract_instance.total_supply_at(&(start - 1)), 0);
+        assert_eq!(contract_instance.balance_at(&other, &start), 0);
+    }
+
+    #[test]
+    fn test_checkpoints_share_a_ledger() {
+        let (env, contract_instance, contract_id, _) = setup();
+        let (_, holder, other) = generate_addresses(&env);
+
+        env.ledger().set_sequence_number(100);
+        contract_instance.mint(&holder, &500);
+        contract_instance.transfer(&holder, &other, &100);
+        contract_instance.transfer(&holder, &other, &50);
+        env.ledger().set_sequence_number(101);
+
+        assert_eq!(contract_instance.balance_at(&holder, &100), 350);
+        assert_eq!(contract_instance.balance_at(&other, &100), 150);
+        env.as_contract(&contract_id, || {
+            let checkpoints: Checkpoints = env
+                .storage()
+                .persistent()
+                .get(&DataKey::BalanceCheckpoints(holder.clone()))
+                .unwrap();
+            assert_eq!(checkpoints.pages, 0);
+            assert_eq!(checkpoints.recent.len(), 1);
+        });
+    }
+
+    #[test]
+    fn test_checkpoint_lookup_must_be_in_the_past() {
+        let (env, contract_instance, _, _) = setup();
+        let (_, holder, _) = generate_addresses(&env);
+        env.ledger().set_sequence_number(100);
+        contract_instance.mint(&holder, &500);
+
+        let res = contract_instance.try_balance_at(&holder, &100);
+        assert_eq!(res, Err(Ok(Sep0041Error::FutureLookup.into())));
+        let res = contract_instance.try_total_supply_at(&101);
+        assert_eq!(res, Err(Ok(Sep0041Error::FutureLookup.into())));
+        assert_eq!(contract_instance.balance_at(&holder, &99), 0);
+    }
+
+    #[test]
+    fn test_checkpoints_for_balances_from_before_checkpoints() {
+        let (env, contract_instance, contract_id, _) = setup();
+        let (_, holder, other) = generate_addresses(&env);
+
+        // a balance written without any checkpoint, as older builds did
+        env.as_contract(&contract_id, || {
+            env.storage()
+                .persistent()
+                .set(&DataKey::Balance(holder.clone()), &400_i128);
+            env.storage()
+                .instance()
+                .set(&DataKey::TotalSupply, &400_i128);
+        });
+        env.ledger().set_sequence_number(50);
+        assert_eq!(contract_instance.shares_of(&holder), 400 * SHARES_PER_TOKEN);
+        assert_eq!(contract_instance.balance_at(&holder, &10), 400);
+        assert_eq!(contract_instance.total_supply_at(&10), 400);
+
+        contract_instance.transfer(&holder, &other, &100);
+        contract_instance.burn(&holder, &100);
+        env.ledger().set_sequence_number(51);
+
+        assert_eq!(contract_instance.balance_at(&holder, &10), 400);
+        assert_eq!(contract_instance.balance_at(&holder, &50), 200);
+        assert_eq!(contract_instance.balance_at(&other, &10), 0);
+        assert_eq!(contract_instance.balance_at(&other, &50), 100);
+        assert_eq!(contract_instance.total_supply_at(&10), 400);
+        assert_eq!(contract_instance.total_supply_at(&50), 300);
+
+        // the whole token entries are gone once shares are written
+        env.as_contract(&contract_id, || {
+            assert!(!env
+                .storage()
+                .persistent()
+                .has(&DataKey::Balance(holder.clone())));
+            assert!(!env.storage().instance().has(&DataKey::TotalSupply));
+        });
+    }
+
+    #[test]
+    fn test_delegate_to_self() {
+        let (env, contract_instance, contract_id, _) = setup();
+        let (_, holder, other) = generate_addresses(&env);
+
+        contract_instance.mint(&holder, &500);
+        // balances only count once delegated
+        assert_eq!(contract_instance.get_votes(&holder), 0);
+        assert_eq!(contract_instance.delegates(&holder), None);
+
+        contract_instance.delegate(&holder, &holder);
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("delegate"), holder.clone()).into_val(&env),
+                    (None::<Address>, holder.clone()).into_val(&env),
+                ),
+                (
+                    contract_id.clone(),
+                    (Symbol::new(&env, "delegate_votes_changed"), holder.clone()).into_val(&env),
+                    (0_i128, 500_i128).into_val(&env),
+                ),
+            ]
+        );
+        assert_eq!(contract_instance.delegates(&holder), Some(holder.clone()));
+        assert_eq!(contract_instance.get_votes(&holder), 500);
+
+        // voting power follows transfers, mints and burns
+        contract_instance.transfer(&holder, &other, &200);
+        assert_eq!(contract_instance.get_votes(&holder), 300);
+        assert_eq!(contract_instance.get_votes(&other), 0);
+        contract_instance.mint(&holder, &50);
+        assert_eq!(contract_instance.get_votes(&holder), 350);
+        contract_instance.burn(&holder, &100);
+        assert_eq!(contract_instance.get_votes(&holder), 250);
+        contract_instance.transfer(&other, &holder, &20);
+        assert_eq!(contract_instance.get_votes(&holder), 270);
+    }
+
+    #[test]
+    fn test_redelegate_moves_votes() {
+        let (env, contract_instance, contract_id, _) = setup();
+        let (_, holder, first_delegate) = generate_addresses(&env);
+        let (_, other_holder, second_delegate) = generate_addresses(&env);
+
+        contract_instance.mint(&holder, &500);
+        contract_instance.mint(&other_holder, &100);
+        contract_instance.delegate(&holder, &first_delegate);
+        contract_instance.delegate(&other_holder, &first_delegate);
+        assert_eq!(contract_instance.get_votes(&first_delegate), 600);
+        // the delegate holds no tokens of its own
+        assert_eq!(contract_instance.balance(&first_delegate), 0);
+
+        contract_instance.delegate(&holder, &second_delegate);
+        assert_eq!(
+            env.events().all().slice(0..1),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("delegate"), holder.clone()).into_val(&env),
+                    (Some(first_delegate.clone()), second_delegate.clone()).into_val(&env),
+                ),
+            ]
+        );
+        assert_eq!(contract_instance.get_votes(&first_delegate), 100);
+        assert_eq!(contract_instance.get_votes(&second_delegate), 500);
+
+        // both sides delegated, the transfer moves votes between delegates
+        contract_instance.transfer(&holder, &other_holder, &150);
+        assert_eq!(contract_instance.get_votes(&first_delegate), 250);
+        assert_eq!(contract_instance.get_votes(&second_delegate), 350);
+
+        // delegating again to the same delegate changes nothing
+        contract_instance.delegate(&holder, &second_delegate);
+        assert_eq!(env.events().all().len(), 1);
+        assert_eq!(contract_instance.get_votes(&second_delegate), 350);
+    }
+
+    #[test]
+    fn test_get_past_votes() {
+        let (env, contract_instance, _, _) = setup();
+        let (_, holder, delegatee) = generate_addresses(&env);
+        let other: Address = Address::generate(&env);
+
+        env.ledger().set_sequence_number(10);
+        contract_instance.mint(&holder, &500);
+        env.ledger().set_sequence_number(20);
+        contract_instance.delegate(&holder, &delegatee);
+        env.ledger().set_sequence_number(30);
+        contract_instance.transfer(&holder, &other, &200);
+        env.ledger().set_sequence_number(40);
+        contract_instance.delegate(&holder, &holder);
+        env.ledger().set_sequence_number(50);
+
+        assert_eq!(contract_instance.get_past_votes(&delegatee, &15), 0);
+        assert_eq!(contract_instance.get_past_votes(&delegatee, &20), 500);
+        assert_eq!(contract_instance.get_past_votes(&delegatee, &29), 500);
+        assert_eq!(contract_instance.get_past_votes(&delegatee, &30), 300);
+        assert_eq!(contract_instance.get_past_votes(&delegatee, &40), 0);
+        assert_eq!(contract_instance.get_past_votes(&holder, &39), 0);
+        assert_eq!(contract_instance.get_past_votes(&holder, &49), 300);
+        assert_eq!(contract_instance.get_votes(&delegatee), 0);
+
+        let res = contract_instance.try_get_past_votes(&holder, &50);
+        assert_eq!(res, Err(Ok(Sep0041Error::FutureLookup.into())));
+    }
+
+    fn setup_with_fee(fee_bps: u32) -> (Env, Sep0041Client<'static>, Address, Address) {
+        let (env, contract_instance, contract_id, _) = setup();
+        let collector: Address = Address::generate(&env);
+        contract_instance.set_transfer_fee(&fee_bps, &collector);
+        (env, contract_instance, contract_id, collector)
+    }
+
+    #[test]
+    fn test_transfer_fee() {
+        let (env, contract_instance, contract_id, collector) = setup_with_fee(250);
+        let (_, from, to) = generate_addresses(&env);
+        assert_eq!(contract_instance.transfer_fee(), 250);
+        assert_eq!(contract_instance.fee_collector(), Some(collector.clone()));
+
+        contract_instance.mint(&from, &1000);
+        contract_instance.transfer(&from, &to, &1000);
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("transfer"), from.clone(), to.clone()).into_val(&env),
+                    975_i128.into_val(&env),
+                ),
+                (
+                    contract_id.clone(),
+                    (symbol_short!("transfer"), from.clone(), collector.clone()).into_val(&env),
+                    25_i128.into_val(&env),
+                ),
+                (
+                    contract_id.clone(),
+                    (Symbol::new(&env, "transfer_fee"), from.clone(), to.clone()).into_val(&env),
+                    (1000_i128, 25_i128, 975_i128).into_val(&env),
+                ),
+            ]
+        );
+        assert_eq!(contract_instance.balance(&from), 0);
+        assert_eq!(contract_instance.balance(&to), 975);
+        assert_eq!(contract_instance.balance(&collector), 25);
+        assert_eq!(contract_instance.total_supply(), 1000);
+    }
+
+    #[test]
+    fn test_transfer_from_pays_fee_out_of_gross_amount() {
+        let (env, contract_instance, _, collector) = setup_with_fee(100);
+        let (_, owner, spender) = generate_addresses(&env);
+        let recipient: Address = Address::generate(&env);
+
+        contract_instance.mint(&owner, &1000);
+        contract_instance.approve(&owner, &spender, &500, &100);
+        contract_instance.transfer_from(&spender, &owner, &recipient, &500);
+
+        assert_eq!(contract_instance.allowance(&owner, &spender), 0);
+        assert_eq!(contract_instance.balance(&owner), 500);
+        assert_eq!(contract_instance.balance(&recipient), 495);
+        assert_eq!(contract_instance.balance(&collector), 5);
+    }
+
+    #[test]
+    fn test_fee_exempt_transfers() {
+        let (env, contract_instance, _, collector) = setup_with_fee(100);
+        let (_, exempt, other) = generate_addresses(&env);
+
+        contract_instance.mint(&exempt, &1000);
+        contract_instance.set_fee_exempt(&exempt, &true);
+        assert!(contract_instance.fee_exempt(&exempt));
+
+        // exempt on either side
+        contract_instance.transfer(&exempt, &other, &500);
+        assert_eq!(contract_instance.balance(&other), 500);
+        contract_instance.transfer(&other, &exempt, &100);
+        assert_eq!(contract_instance.balance(&exempt), 600);
+        assert_eq!(contract_instance.balance(&collector), 0);
+
+        // the collector never pays itself
+        contract_instance.transfer(&other, &collector, &100);
+        assert_eq!(contract_instance.balance(&collector), 100);
+        contract_instance.transfer(&collector, &other, &100);
+        assert_eq!(contract_instance.balance(&other), 400);
+
+        contract_instance.set_fee_exempt(&exempt, &false);
+        assert!(!contract_instance.fee_exempt(&exempt));
+        contract_instance.transfer(&exempt, &other, &100);
+        assert_eq!(contract_instance.balance(&other), 499);
+        assert_eq!(contract_instance.balance(&collector), 1);
+    }
+
+    #[test]
+    fn test_fee_collector_must_be_authorized() {
+        let (env, contract_instance, _, collector) = setup_with_fee(100);
+        let (_, from, to) = generate_addresses(&env);
+        contract_instance.mint(&from, &1000);
+
+        // a collector that cannot receive turns fee paying transfers away
+        contract_instance.set_authorized(&collector, &false);
+        assert_eq!(
+            contract_instance.try_transfer(&from, &to, &500),
+            Err(Ok(Sep0041Error::Unauthorized.into()))
+        );
+        assert_eq!(contract_instance.balance(&from), 1000);
+        assert_eq!(
+            contract_instance.try_set_transfer_fee(&100, &collector),
+            Err(Ok(Sep0041Error::Unauthorized.into()))
+        );
+
+        contract_instance.set_authorized(&collector, &true);
+        contract_instance.transfer(&from, &to, &500);
+        assert_eq!(contract_instance.balance(&collector), 5);
+    }
+
+    #[test]
+    fn test_transfer_fee_rounding_and_cap() {
+        let (env, contract_instance, _, collector) = setup_with_fee(MAX_FEE_BPS);
+        let (_, from, to) = generate_addresses(&env);
+
+        let res = contract_instance.try_set_transfer_fee(&(MAX_FEE_BPS + 1), &collector);
+        assert_eq!(res, Err(Ok(Sep0041Error::InvalidFee.into())));
+        assert_eq!(contract_instance.transfer_fee(), MAX_FEE_BPS);
+
+        let max_amount: i128 = i128::MAX / SHARES_PER_TOKEN;
+        contract_instance.mint(&from, &max_amount);
+        // rounds down, below 10 units the fee is nothing
+        contract_instance.transfer(&from, &to, &9);
+        assert_eq!(contract_instance.balance(&collector), 0);
+        contract_instance.transfer(&from, &to, &19);
+        assert_eq!(contract_instance.balance(&collector), 1);
+
+        // no overflow on the largest amounts
+        let rest: i128 = contract_instance.balance(&from);
+        contract_instance.transfer(&from, &to, &rest);
+        assert_eq!(contract_instance.balance(&collector), 1 + rest / 10);
+        assert_eq!(
+            contract_instance.balance(&to) + contract_instance.balance(&collector),
+            max_amount
+        );
+
+        contract_instance.set_transfer_fee(&0, &collector);
+        contract_instance.transfer(&to, &from, &1000);
+        assert_eq!(contract_instance.balance(&from), 1000);
+    }
+
+    #[test]
+    fn test_transfer_fee_keeps_supply_invariant() {
+        let (env, contract_instance, _, collector) = setup_with_fee(37);
+        let (_, first, second) = generate_addresses(&env);
+        let third: Address = Address::generate(&env);
+
+        contract_instance.mint(&first, &1_000_003);
+        contract_instance.transfer(&first, &second, &333_331);
+        contract_instance.approve(&second, &third, &100_000, &100);
+        contract_instance.transfer_from(&third, &second, &first, &99_999);
+        contract_instance.batch_transfer(
+            &first,
+            &vec![
+                &env,
+                (second.clone(), 12_345_i128),
+                (third.clone(), 777_i128),
+            ],
+        );
+        contract_instance.burn(&third, &100);
+
+        let sum: i128 = contract_instance.balance(&first)
+            + contract_instance.balance(&second)
+            + contract_instance.balance(&third)
+            + contract_instance.balance(&collector);
+        assert_eq!(sum, contract_instance.total_supply());
+        assert!(contract_instance.balance(&collector) > 0);
+    }
+
+    #[test]
+    fn test_lock_blocks_spending() {
+        let (env, contract_instance, contract_id, _) = setup();
+        let (_, holder, other) = generate_addresses(&env);
+        env.ledger().set_sequence_number(10);
+
+        contract_instance.mint(&holder, &1000);
+        contract_instance.lock(&holder, &600, &100);
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("lock"), holder.clone()).into_val(&env),
+                    (600_i128, 100_u32).into_val(&env),
+                ),
+            ]
+        );
+        assert_eq!(contract_instance.balance(&holder), 1000);
+        assert_eq!(contract_instance.locked_balance(&holder), 600);
+        assert_eq!(contract_instance.spendable_balance(&holder), 400);
+
+        let res = contract_instance.try_transfer(&holder, &other, &401);
+        assert_eq!(res, Err(Ok(Sep0041Error::BalanceLocked.into())));
+        contract_instance.transfer(&holder, &other, &300);
+        let res = contract_instance.try_burn(&holder, &101);
+        assert_eq!(res, Err(Ok(Sep0041Error::BalanceLocked.into())));
+        contract_instance.burn(&holder, &100);
+        assert_eq!(contract_instance.spendable_balance(&holder), 0);
+
+        contract_instance.approve(&holder, &other, &100, &200);
+        let res = contract_instance.try_transfer_from(&other, &holder, &other, &1);
+        assert_eq!(res, Err(Ok(Sep0041Error::BalanceLocked.into())));
+        let res = contract_instance.try_batch_transfer(&holder, &vec![&env, (other.clone(), 1)]);
+        assert_eq!(res, Err(Ok(Sep0041Error::BalanceLocked.into())));
+
+        // spendable from the unlock ledger on, released or not
+        env.ledger().set_sequence_number(100);
+        assert_eq!(contract_instance.locked_balance(&holder), 0);
+        contract_instance.transfer(&holder, &other, &600);
+        assert_eq!(contract_instance.balance(&holder), 0);
+    }
+
+    #[test]
+    fn test_mint_locked_with_concurrent_locks() {
+        let (env, contract_instance, contract_id, admin) = setup();
+        let (_, holder, other) = generate_addresses(&env);
+        env.ledger().set_sequence_number(10);
+
+        contract_instance.mint(&holder, &100);
+        contract_instance.mint_locked(&holder, &300, &50);
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("mint"), admin.clone(), holder.clone()).into_val(&env),
+                    300_i128.into_val(&env),
+                ),
+                (
+                    contract_id.clone(),
+                    (symbol_short!("lock"), holder.clone()).into_val(&env),
+                    (300_i128, 50_u32).into_val(&env),
+                ),
+            ]
+        );
+        contract_instance.mint_locked(&holder, &200, &100);
+        contract_instance.lock(&holder, &50, &150);
+
+        assert_eq!(contract_instance.balance(&holder), 600);
+        assert_eq!(contract_instance.locked_balance(&holder), 550);
+        assert_eq!(contract_instance.spendable_balance(&holder), 50);
+
+        // locks expire one by one, a cliff followed by later tranches
+        env.ledger().set_sequence_number(50);
+        assert_eq!(contract_instance.spendable_balance(&holder), 350);
+        env.ledger().set_sequence_number(100);
+        assert_eq!(contract_instance.spendable_balance(&holder), 550);
+        contract_instance.transfer(&holder, &other, &550);
+        let res = contract_instance.try_transfer(&holder, &other, &1);
+        assert_eq!(res, Err(Ok(Sep0041Error::BalanceLocked.into())));
+        env.ledger().set_sequence_number(150);
+        assert_eq!(contract_instance.spendable_balance(&holder), 50);
+    }
+
+    #[test]
+    fn test_release_consolidates_expired_locks() {
+        let (env, contract_instance, contract_id, _) = setup();
+        let (_, holder, _) = generate_addresses(&env);
+        env.ledger().set_sequence_number(10);
+
+        contract_instance.mint(&holder, &1000);
+        contract_instance.lock(&holder, &100, &20);
+        contract_instance.lock(&holder, &200, &30);
+        contract_instance.lock(&holder, &300, &40);
+        assert_eq!(contract_instance.release(&holder), 0);
+
+        env.ledger().set_sequence_number(30);
+        assert_eq!(contract_instance.release(&holder), 300);
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("release"), holder.clone()).into_val(&env),
+                    300_i128.into_val(&env),
+                ),
+            ]
+        );
+        assert_eq!(contract_instance.release(&holder), 0);
+        assert_eq!(contract_instance.locked_balance(&holder), 300);
+        env.as_contract(&contract_id, || {
+            let locks: Vec<Lock> = env
+                .storage()
+                .persistent()
+                .get(&DataKey::Locks(holder.clone()))
+                .unwrap();
+            assert_eq!(
+                locks,
+                vec![
+                    &env,
+                    Lock {
+                        amount: 300,
+                        unlock_ledger: 40
+                    }
+                ]
+            );
+        });
+
+        env.ledger().set_sequence_number(40);
+        assert_eq!(contract_instance.release(&holder), 300);
+        env.as_contract(&contract_id, || {
+            assert!(!env
+                .storage()
+                .persistent()
+                .has(&DataKey::Locks(holder.clone())));
+        });
+    }
+
+    #[test]
+    fn test_lock_validation() {
+        let (env, contract_instance, _, _) = setup();
+        let (_, holder, _) = generate_addresses(&env);
+        env.ledger().set_sequence_number(10);
+        contract_instance.mint(&holder, &1000);
+
+        let res = contract_instance.try_lock(&holder, &100, &10);
+        assert_eq!(res, Err(Ok(Sep0041Error::InvalidUnlockLedger.into())));
+        let res = contract_instance.try_mint_locked(&holder, &100, &5);
+        assert_eq!(res, Err(Ok(Sep0041Error::InvalidUnlockLedger.into())));
+        let res = contract_instance.try_lock(&holder, &1001, &20);
+        assert_eq!(res, Err(Ok(Sep0041Error::InsufficientBalance.into())));
+
+        // tokens can only be locked once
+        contract_instance.lock(&holder, &800, &20);
+        let res = contract_instance.try_lock(&holder, &201, &20);
+        assert_eq!(res, Err(Ok(Sep0041Error::BalanceLocked.into())));
+
+        for _ in 1..MAX_LOCKS {
+            contract_instance.lock(&holder, &1, &30);
+        }
+        let res = contract_instance.try_lock(&holder, &1, &30);
+        assert_eq!(res, Err(Ok(Sep0041Error::TooManyLocks.into())));
+
+        // expired locks are dropped to make room
+        env.ledger().set_sequence_number(20);
+        contract_instance.lock(&holder, &1, &30);
+        assert_eq!(contract_instance.locked_balance(&holder), MAX_LOCKS as i128);
+    }
+
+    #[test]
+    fn test_clawback_takes_locked_tokens() {
+        let (env, contract_instance, _, _) = setup();
+        let (_, holder, _) = generate_addresses(&env);
+        env.ledger().set_sequence_number(10);
+
+        contract_instance.mint_locked(&holder, &500, &100);
+        contract_instance.clawback(&holder, &300);
+
+        assert_eq!(contract_instance.balance(&holder), 200);
+        assert_eq!(contract_instance.locked_balance(&holder), 200);
+        assert_eq!(contract_instance.spendable_balance(&holder), 0);
+    }
+
+    fn stored_allowance(
+        env: &Env,
+        contract_id: &Address,
+        owner: &Address,
+        spender: &Address,
+    ) -> Option<AllowanaceDetails> {
+        env.as_contract(contract_id, || {
+            env.storage()
+                .temporary()
+                .get(&DataKey::Allowance(owner.clone(), spender.clone()))
+        })
+    }
+
+    #[test]
+    fn test_approve_zero_revokes() {
+        let (env, contract_instance, contract_id, _) = setup();
+        let (_, owner, spender) = generate_addresses(&env);
+        contract_instance.mint(&owner, &500);
+
+        contract_instance.approve(&owner, &spender, &100, &50);
+        contract_instance.approve(&owner, &spender, &0, &50);
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("approve"), owner.clone(), spender.clone()).into_val(&env),
+                    (0_i128, 50_u32).into_val(&env),
+                ),
+            ]
+        );
+        assert_eq!(contract_instance.allowance(&owner, &spender), 0);
+        assert!(stored_allowance(&env, &contract_id, &owner, &spender).is_none());
+        let res = contract_instance.try_transfer_from(&spender, &owner, &spender, &1);
+        assert_eq!(res, Err(Ok(Sep0041Error::InsufficientAllowance.into())));
+
+        let res = contract_instance.try_approve(&owner, &spender, &-1, &50);
+        assert_eq!(res, Err(Ok(Sep0041Error::InvalidAmount.into())));
+    }
+
+    #[test]
+    fn test_increase_allowance() {
+        let (env, contract_instance, contract_id, _) = setup();
+        let (_, owner, spender) = generate_addresses(&env);
+        env.ledger().set_sequence_number(10);
+
+        let res = contract_instance.try_increase_allowance(&owner, &spender, &50);
+        assert_eq!(res, Err(Ok(Sep0041Error::InsufficientAllowance.into())));
+
+        contract_instance.approve(&owner, &spender, &100, &50);
+        contract_instance.increase_allowance(&owner, &spender, &50);
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("approve"), owner.clone(), spender.clone()).into_val(&env),
+                    (150_i128, 50_u32).into_val(&env),
+                ),
+            ]
+        );
+        assert_eq!(
+            stored_allowance(&env, &contract_id, &owner, &spender),
+            Some(AllowanaceDetails {
+                amount: 150,
+                expiration_ledger: 50
+            })
+        );
+
+        let res = contract_instance.try_increase_allowance(&owner, &spender, &0);
+        assert_eq!(res, Err(Ok(Sep0041Error::InvalidAmount.into())));
+        let res = contract_instance.try_increase_allowance(&owner, &spender, &i128::MAX);
+        assert_eq!(res, Err(Ok(Sep0041Error::Overflow.into())));
+
+        // the temporary entry lives exactly until the expiration, past it nothing is left
+        env.ledger().set_sequence_number(51);
+        let res = contract_instance.try_increase_allowance(&owner, &spender, &50);
+        assert_eq!(res, Err(Ok(Sep0041Error::InsufficientAllowance.into())));
+    }
+
+    #[test]
+    fn test_decrease_allowance() {
+        let (env, contract_instance, contract_id, _) = setup();
+        let (_, owner, spender) = generate_addresses(&env);
+        env.ledger().set_sequence_number(10);
+
+        contract_instance.approve(&owner, &spender, &150, &50);
+        contract_instance.decrease_allowance(&owner, &spender, &100);
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("approve"), owner.clone(), spender.clone()).into_val(&env),
+                    (50_i128, 50_u32).into_val(&env),
+                ),
+            ]
+        );
+        assert_eq!(contract_instance.allowance(&owner, &spender), 50);
+
+        let res = contract_instance.try_decrease_allowance(&owner, &spender, &51);
+        assert_eq!(res, Err(Ok(Sep0041Error::InsufficientAllowance.into())));
+
+        // down to zero revokes
+        contract_instance.decrease_allowance(&owner, &spender, &50);
+        assert!(stored_allowance(&env, &contract_id, &owner, &spender).is_none());
+        let res = contract_instance.try_decrease_allowance(&owner, &spender, &1);
+        assert_eq!(res, Err(Ok(Sep0041Error::InsufficientAllowance.into())));
+    }
+
+    #[test]
+    fn test_revoke() {
+        let (env, contract_instance, contract_id, _) = setup();
+        let (_, owner, spender) = generate_addresses(&env);
+        env.ledger().set_sequence_number(10);
+
+        contract_instance.approve(&owner, &spender, &150, &50);
+        contract_instance.revoke(&owner, &spender);
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("approve"), owner.clone(), spender.clone()).into_val(&env),
+                    (0_i128, 10_u32).into_val(&env),
+                ),
+            ]
+        );
+        assert_eq!(contract_instance.allowance(&owner, &spender), 0);
+        assert!(stored_allowance(&env, &contract_id, &owner, &spender).is_none());
+
+        // revoking twice is harmless
+        contract_instance.revoke(&owner, &spender);
+
+        env.mock_auths(&[]);
+        assert!(contract_instance.try_revoke(&owner, &spender).is_err());
+    }
+
+    #[test]
+    fn test_unlimited_allowance_is_never_decremented() {
+        let (env, contract_instance, contract_id, _) = setup();
+        let (_, owner, spender) = generate_addresses(&env);
+        env.ledger().set_sequence_number(10);
+        contract_instance.mint(&owner, &1000);
+        contract_instance.approve(&owner, &spender, &UNLIMITED_ALLOWANCE, &50);
+
+        contract_instance.transfer_from(&spender, &owner, &spender, &300);
+        // balances, checkpoints and the auth nonce, the allowance is not written
+        assert_eq!(env.cost_estimate().resources().write_entries, 5);
+        contract_instance.burn_from(&spender, &owner, &200);
+        assert_eq!(
+            stored_allowance(&env, &contract_id, &owner, &spender),
+            Some(AllowanaceDetails {
+                amount: UNLIMITED_ALLOWANCE,
+                expiration_ledger: 50
+            })
+        );
+        assert_eq!(contract_instance.balance(&owner), 500);
+
+        // it still expires
+        env.ledger().set_sequence_number(51);
+        assert_eq!(contract_instance.allowance(&owner, &spender), 0);
+        let res = contract_instance.try_transfer_from(&spender, &owner, &spender, &1);
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn test_decrease_unlimited_allowance() {
+        let (env, contract_instance, _, _) = setup();
+        let (_, owner, spender) = generate_addresses(&env);
+        contract_instance.mint(&owner, &1000);
+        contract_instance.approve(&owner, &spender, &UNLIMITED_ALLOWANCE, &50);
+
+        contract_instance.decrease_allowance(&owner, &spender, &1);
+        contract_instance.transfer_from(&spender, &owner, &spender, &300);
+        assert_eq!(
+            contract_instance.allowance(&owner, &spender),
+            UNLIMITED_ALLOWANCE - 301
+        );
+    }
+
+    fn indexed_spenders(env: &Env, contract_id: &Address, owner: &Address) -> Vec<Address> {
+        env.as_contract(contract_id, || {
+            env.storage()
+                .persistent()
+                .get(&DataKey::Spenders(owner.clone()))
+                .unwrap_or_else(|| Vec::new(env))
+        })
+    }
+
+    #[test]
+    fn test_allowances_of_pagination() {
+        let (env, contract_instance, _, _) = setup();
+        let (owner, first, second) = generate_addresses(&env);
+        let third: Address = Address::generate(&env);
+
+        assert_eq!(
+            contract_instance.allowances_of(&owner, &0, &10).allowances,
+            Vec::new(&env)
+        );
+        contract_instance.approve(&owner, &first, &100, &50);
+        contract_instance.approve(&owner, &second, &200, &60);
+        contract_instance.approve(&owner, &third, &300, &70);
+        // approving again keeps the spender in place
+        contract_instance.approve(&owner, &first, &150, &55);
+        assert!(!contract_instance.allowances_of(&owner, &0, &10).unlisted);
+
+        assert_eq!(
+            contract_instance.allowances_of(&owner, &0, &10).allowances,
+            vec![
+                &env,
+                (first.clone(), 150_i128, 55_u32),
+                (second.clone(), 200_i128, 60_u32),
+                (third.clone(), 300_i128, 70_u32),
+            ]
+        );
+        assert_eq!(
+            contract_instance.allowances_of(&owner, &1, &1).allowances,
+            vec![&env, (second.clone(), 200_i128, 60_u32)]
+        );
+        assert_eq!(
+            contract_instance.allowances_of(&owner, &2, &5).allowances,
+            vec![&env, (third.clone(), 300_i128, 70_u32)]
+        );
+        assert_eq!(
+            contract_instance.allowances_of(&owner, &3, &5).allowances,
+            Vec::new(&env)
+        );
+        assert_eq!(
+            contract_instance
+                .allowances_of(&owner, &u32::MAX, &u32::MAX)
+                .allowances,
+            Vec::new(&env)
+        );
+    }
+
+    #[test]
+    fn test_allowances_of_prunes_stale_spenders() {
+        let (env, contract_instance, contract_id, _) = setup();
+        let (owner, revoked, spent) = generate_addresses(&env);
+        let (expiring, live, _) = generate_addresses(&env);
+        env.ledger().set_sequence_number(10);
+        contract_instance.mint(&owner, &1000);
+
+        contract_instance.approve(&owner, &revoked, &100, &50);
+        contract_instance.approve(&owner, &spent, &100, &50);
+        contract_instance.approve(&owner, &expiring, &100, &20);
+        contract_instance.approve(&owner, &live, &100, &50);
+
+        // revoking and spending everything drop the spender right away
+        contract_instance.revoke(&owner, &revoked);
+        contract_instance.transfer_from(&spent, &owner, &spent, &100);
+        assert_eq!(
+            indexed_spenders(&env, &contract_id, &owner),
+            vec![&env, expiring.clone(), live.clone()]
+        );
+
+        // expired ones once they are listed
+        env.ledger().set_sequence_number(21);
+        assert_eq!(
+            contract_instance.allowances_of(&owner, &0, &10).allowances,
+            vec![&env, (live.clone(), 100_i128, 50_u32)]
+        );
+        assert_eq!(
+            indexed_spenders(&env, &contract_id, &owner),
+            vec![&env, live.clone()]
+        );
+
+        contract_instance.decrease_allowance(&owner, &live, &100);
+        env.as_contract(&contract_id, || {
+            assert!(!env
+                .storage()
+                .persistent()
+                .has(&DataKey::Spenders(owner.clone())));
+        });
+    }
+
+    #[test]
+    fn test_spender_index_is_bounded() {
+        let (env, contract_instance, _, _) = setup();
+        let owner: Address = Address::generate(&env);
+        env.ledger().set_sequence_number(10);
+
+        contract_instance.approve(&owner, &Address::generate(&env), &100, &20);
+        for _ in 1..MAX_SPENDERS {
+            contract_instance.approve(&owner, &Address::generate(&env), &100, &50);
+        }
+        // a full index does not stand in the way of an approval, it is just not listed
+        let unlisted: Address = Address::generate(&env);
+        contract_instance.approve(&owner, &unlisted, &100, &50);
+        assert_eq!(contract_instance.allowance(&owner, &unlisted), 100);
+        let page = contract_instance.allowances_of(&owner, &0, &(MAX_SPENDERS + 1));
+        assert_eq!(page.allowances.len(), MAX_SPENDERS);
+        assert!(!page
+            .allowances
+            .iter()
+            .any(|(listed, _, _)| listed == unlisted));
+        // but the page tells there is more
+        assert!(page.unlisted);
+
+        // an expired approval makes room
+        env.ledger().set_sequence_number(21);
+        let spender: Address = Address::generate(&env);
+        contract_instance.approve(&owner, &spender, &100, &50);
+        let page = contract_instance.allowances_of(&owner, &0, &MAX_SPENDERS);
+        assert_eq!(page.allowances.len(), MAX_SPENDERS);
+        assert_eq!(
+            page.allowances.last().unwrap(),
+            (spender.clone(), 100_i128, 50_u32)
+        );
+        assert!(page.unlisted);
+
+        // until the unlisted approval has expired as well
+        env.ledger().set_sequence_number(51);
+        assert!(!contract_instance.allowances_of(&owner, &0, &1).unlisted);
+    }
+
+    fn setup_wrapped() -> (Env, Sep0041Client<'static>, Address, TokenClient<'static>) {
+        let env: Env = Env::default();
+        env.mock_all_auths();
+
+        let (admin, issuer, _) = generate_addresses(&env);
+        let underlying = env.register_stellar_asset_contract_v2(issuer);
+        let contract_id = env.register(
+            Sep0041,
+            (
+                admin,
+                7_u32,
+                String::from_str(&env, "wrapped"),
+                String::from_str(&env, "wlsd"),
+                false,
+                None::<i128>,
+                Some(underlying.address()),
+            ),
+        );
+        let contract_client = Sep0041Client::new(&env, &contract_id);
+        let underlying_client = TokenClient::new(&env, &underlying.address());
+        (env, contract_client, contract_id, underlying_client)
+    }
+
+    #[test]
+    fn test_wrapped_deposit_and_withdraw() {
+        let (env, contract_instance, contract_id, underlying) = setup_wrapped();
+        let user: Address = Address::generate(&env);
+        StellarAssetClient::new(&env, &underlying.address).mint(&user, &1000);
+        assert_eq!(
+            contract_instance.underlying(),
+            Some(underlying.address.clone())
+        );
+
+        contract_instance.deposit(&user, &400);
+        assert_eq!(contract_instance.balance(&user), 400);
+        assert_eq!(underlying.balance(&user), 600);
+        assert_eq!(underlying.balance(&contract_id), 400);
+        assert_eq!(
+            contract_instance.reserves(),
+            contract_instance.total_supply()
+        );
+
+        contract_instance.withdraw(&user, &150);
+        assert_eq!(contract_instance.balance(&user), 250);
+        assert_eq!(underlying.balance(&user), 750);
+        assert_eq!(contract_instance.reserves(), 250);
+        assert_eq!(
+            contract_instance.reserves(),
+            contract_instance.total_supply()
+        );
+
+        // wrapped tokens move like any other and stay redeemable by whoever holds them
+        let other: Address = Address::generate(&env);
+        contract_instance.transfer(&user, &other, &100);
+        contract_instance.withdraw(&other, &100);
+        assert_eq!(underlying.balance(&other), 100);
+        assert_eq!(
+            contract_instance.reserves(),
+            contract_instance.total_supply()
+        );
+
+        let res = contract_instance.try_withdraw(&user, &151);
+        assert_eq!(res, Err(Ok(Sep0041Error::InsufficientBalance.into())));
+        assert!(contract_instance.try_deposit(&user, &751).is_err());
+        assert_eq!(contract_instance.total_supply(), 150);
+    }
+
+    #[test]
+    fn test_wrapped_supply_only_grows_through_deposit() {
+        let (env, contract_instance, _, underlying) = setup_wrapped();
+        let user: Address = Address::generate(&env);
+        StellarAssetClient::new(&env, &underlying.address).mint(&user, &1000);
+        contract_instance.deposit(&user, &500);
+        contract_instance.approve(&user, &user, &100, &200);
+
+        let wrapped = Err(Ok(Sep0041Error::WrappedSupply.into()));
+        assert_eq!(contract_instance.try_mint(&user, &10), wrapped);
+        assert_eq!(contract_instance.try_clawback(&user, &10), wrapped);
+        assert_eq!(contract_instance.try_mint_locked(&user, &10, &100), wrapped);
+        assert_eq!(
+            contract_instance.reserves(),
+            contract_instance.total_supply()
+        );
+
+        // burning gives up backed tokens, the reserves stay behind
+        contract_instance.burn(&user, &10);
+        contract_instance.burn_from(&user, &user, &10);
+        assert_eq!(contract_instance.total_supply(), 480);
+        assert_eq!(contract_instance.reserves(), 500);
+        contract_instance.withdraw(&user, &480);
+        assert_eq!(contract_instance.reserves(), 20);
+    }
+
+    #[test]
+    fn test_wrapped_reserves_can_exceed_supply() {
+        let (env, contract_instance, contract_id, underlying) = setup_wrapped();
+        let user: Address = Address::generate(&env);
+        StellarAssetClient::new(&env, &underlying.address).mint(&user, &1000);
+        contract_instance.deposit(&user, &300);
+
+        // underlying sent straight to the contract is not minted and does not block withdrawals
+        underlying.transfer(&user, &contract_id, &50);
+        assert_eq!(contract_instance.reserves(), 350);
+        contract_instance.withdraw(&user, &300);
+        assert_eq!(contract_instance.total_supply(), 0);
+        assert_eq!(contract_instance.reserves(), 50);
+    }
+
+    #[test]
+    fn test_deposit_requires_an_underlying_token() {
+        let (env, contract_instance, _, _) = setup();
+        let user: Address = Address::generate(&env);
+        assert_eq!(contract_instance.underlying(), None);
+        assert_eq!(contract_instance.reserves(), 0);
+        let res = contract_instance.try_deposit(&user, &10);
+        assert_eq!(res, Err(Ok(Sep0041Error::NotWrapped.into())));
+        let res = contract_instance.try_withdraw(&user, &10);
+        assert_eq!(res, Err(Ok(Sep0041Error::NotWrapped.into())));
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #15)")]
+    fn test_wrapped_decimals_must_match_underlying() {
+        let env: Env = Env::default();
+        let (admin, issuer, _) = generate_addresses(&env);
+        let underlying = env.register_stellar_asset_contract_v2(issuer);
+        env.register(
+            Sep0041,
+            (
+                admin,
+                18_u32,
+                String::from_str(&env, "wrapped"),
+                String::from_str(&env, "wlsd"),
+                false,
+                None::<i128>,
+                Some(underlying.address()),
+            ),
+        );
+    }
+
+    #[test]
+    fn test_rebase_scales_balances_and_supply() {
+        let (env, contract_instance, contract_id, admin) = setup();
+        let (holder, other_holder, spender) = generate_addresses(&env);
+        contract_instance.mint(&holder, &1000);
+        contract_instance.mint(&other_holder, &500);
+        contract_instance.approve(&holder, &spender, &300, &200);
+        assert_eq!(contract_instance.rebase_index(), REBASE_INDEX_ONE);
+
+        contract_instance.rebase(&admin, &(REBASE_INDEX_ONE * 3 / 2));
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("rebase"), admin.clone()).into_val(&env),
+                    (REBASE_INDEX_ONE, REBASE_INDEX_ONE * 3 / 2).into_val(&env),
+                ),
+            ]
+        );
+        assert_eq!(contract_instance.balance(&holder), 1500);
+        assert_eq!(contract_instance.balance(&other_holder), 750);
+        assert_eq!(contract_instance.total_supply(), 2250);
+        assert_eq!(
+            contract_instance.shares_of(&holder),
+            1000 * SHARES_PER_TOKEN
+        );
+        assert_eq!(contract_instance.total_shares(), 1500 * SHARES_PER_TOKEN);
+
+        // allowances are in tokens and do not rebase
+        assert_eq!(contract_instance.allowance(&holder, &spender), 300);
+        contract_instance.transfer_from(&spender, &holder, &spender, &300);
+        assert_eq!(contract_instance.allowance(&holder, &spender), 0);
+        assert_eq!(contract_instance.balance(&spender), 300);
+        assert_eq!(contract_instance.balance(&holder), 1200);
+        assert_eq!(
+            contract_instance.shares_of(&spender),
+            200 * SHARES_PER_TOKEN
+        );
+
+        contract_instance.rebase(&admin, &(REBASE_INDEX_ONE / 2));
+        assert_eq!(contract_instance.balance(&holder), 400);
+        assert_eq!(contract_instance.balance(&other_holder), 250);
+        assert_eq!(contract_instance.balance(&spender), 100);
+        assert_eq!(contract_instance.total_supply(), 750);
+        assert_eq!(contract_instance.total_shares(), 1500 * SHARES_PER_TOKEN);
+    }
+
+    #[test]
+    fn test_rebase_requires_admin_or_oracle() {
+        let (env, contract_instance, _, admin) = setup();
+        let oracle: Address = Address::generate(&env);
+        contract_instance.mint(&admin, &100);
+
+        let res = contract_instance.try_rebase(&oracle, &(REBASE_INDEX_ONE * 2));
+        assert_eq!(res, Err(Ok(Sep0041Error::Unauthorized.into())));
+
+        contract_instance.set_rebase_oracle(&oracle);
+        contract_instance.rebase(&oracle, &(REBASE_INDEX_ONE * 2));
+        assert_eq!(contract_instance.balance(&admin), 200);
+
+        let res = contract_instance.try_rebase(&oracle, &0);
+        assert_eq!(res, Err(Ok(Sep0041Error::InvalidRebaseIndex.into())));
+        let res = contract_instance.try_rebase(&admin, &-1);
+        assert_eq!(res, Err(Ok(Sep0041Error::InvalidRebaseIndex.into())));
+
+        contract_instance.pause(&admin);
+        let res = contract_instance.try_rebase(&oracle, &REBASE_INDEX_ONE);
+        assert_eq!(res, Err(Ok(Sep0041Error::Paused.into())));
+    }
+
+    #[test]
+    fn test_rebase_respects_max_supply_and_wrapped_mode() {
+        let (_, contract_instance, _, admin) = setup_with_config(false, Some(1000));
+        contract_instance.mint(&admin, &800);
+        let res = contract_instance.try_rebase(&admin, &(REBASE_INDEX_ONE * 13 / 10));
+        assert_eq!(res, Err(Ok(Sep0041Error::MaxSupplyExceeded.into())));
+        contract_instance.rebase(&admin, &(REBASE_INDEX_ONE * 5 / 4));
+        assert_eq!(contract_instance.total_supply(), 1000);
+        let res = contract_instance.try_mint(&admin, &2);
+        assert_eq!(res, Err(Ok(Sep0041Error::MaxSupplyExceeded.into())));
+
+        let (_, wrapped_instance, _, _) = setup_wrapped();
+        let res = wrapped_instance.try_rebase(&wrapped_instance.admin(), &(REBASE_INDEX_ONE * 2));
+        assert_eq!(res, Err(Ok(Sep0041Error::WrappedSupply.into())));
+    }
+
+    #[test]
+    fn test_rebase_rounding_invariants() {
+        let (env, contract_instance, contract_id, admin) = setup();
+        let spender: Address = Address::generate(&env);
+        let holders: [Address; 4] = [
+            Address::generate(&env),
+            Address::generate(&env),
+            Address::generate(&env),
+            Address::generate(&env),
+        ];
+        let totals = || {
+            let mut shares: i128 = 0;
+            let mut balances: i128 = 0;
+            for holder in holders.iter() {
+                shares += contract_instance.shares_of(holder);
+                balances += contract_instance.balance(holder);
+            }
+            // shares are conserved
+            assert_eq!(shares, contract_instance.total_shares());
+            (balances, contract_instance.total_supply())
+        };
+
+        // minted at an index that is no round number, every unit still lands
+        contract_instance.rebase(&admin, &1_333_333_333);
+        for (holder, amount) in holders.iter().zip([1001_i128, 2003, 7, 1]) {
+            contract_instance.mint(holder, &amount);
+            let minted: i128 = env.events().all().last().unwrap().2.into_val(&env);
+            assert_eq!(minted, amount);
+            assert_eq!(contract_instance.balance(holder), amount);
+        }
+        assert_eq!(totals(), (3012, 3012));
+
+        for round in 0..24_usize {
+            let from: &Address = &holders[round % 4];
+            let to: &Address = &holders[(round + 1) % 4];
+            let from_balance: i128 = contract_instance.balance(from);
+            let to_balance: i128 = contract_instance.balance(to);
+            let amount: i128 = (round as i128 * 37 + 11) % from_balance + 1;
+
+            contract_instance.approve(from, &spender, &amount, &1000);
+            contract_instance.transfer_from(&spender, from, to, &amount);
+            assert_eq!(
+                env.events().all(),
+                vec![
+                    &env,
+                    (
+                        contract_id.clone(),
+                        (symbol_short!("transfer"), from.clone(), to.clone()).into_val(&env),
+                        amount.into_val(&env),
+                    ),
+                ]
+            );
+            // debits, credits, the allowance and the event all agree to the unit
+            assert_eq!(contract_instance.balance(from), from_balance - amount);
+            assert_eq!(contract_instance.balance(to), to_balance + amount);
+            assert_eq!(contract_instance.allowance(from, &spender), 0);
+
+            // balances held through a rebase round on their own, the drift `rebase` documents
+            let (balances, total_supply) = totals();
+            assert!((total_supply - balances).abs() <= holders.len() as i128 / 2);
+            if round % 6 == 5 {
+                contract_instance.rebase(&admin, &(1_000_000_007 + round as i128 * 77_777_777));
+            }
+        }
+
+        // a whole balance can always be sent or burned
+        let balance: i128 = contract_instance.balance(&holders[1]);
+        contract_instance.transfer(&holders[1], &holders[2], &balance);
+        assert_eq!(contract_instance.balance(&holders[1]), 0);
+        assert_eq!(contract_instance.shares_of(&holders[1]), 0);
+        let balance: i128 = contract_instance.balance(&holders[2]);
+        let total_supply: i128 = contract_instance.total_supply();
+        contract_instance.burn(&holders[2], &balance);
+        assert_eq!(contract_instance.balance(&holders[2]), 0);
+        assert_eq!(contract_instance.total_supply(), total_supply - balance);
+    }
+
+    #[test]
+    fn test_rebase_keeps_history() {
+        let (env, contract_instance, _, admin) = setup();
+        let holder: Address = Address::generate(&env);
+        env.ledger().set_sequence_number(10);
+        contract_instance.mint(&holder, &1000);
+        contract_instance.delegate(&holder, &holder);
+
+        env.ledger().set_sequence_number(20);
+        contract_instance.rebase(&admin, &(REBASE_INDEX_ONE * 2));
+        env.ledger().set_sequence_number(30);
+
+        assert_eq!(contract_instance.balance_at(&holder, &15), 1000);
+        assert_eq!(contract_instance.balance_at(&holder, &25), 2000);
+        assert_eq!(contract_instance.total_supply_at(&15), 1000);
+        assert_eq!(contract_instance.total_supply_at(&25), 2000);
+        assert_eq!(contract_instance.get_past_votes(&holder, &15), 1000);
+        assert_eq!(contract_instance.get_past_votes(&holder, &25), 2000);
+        assert_eq!(contract_instance.get_votes(&holder), 2000);
+    }
 }
@@ -0,0 +1,7 @@
+use soroban_sdk::{contracttype, Address};
+
+#[contracttype]
+pub struct Approve {
+    from: Address,
+    spender: Address,
+}
@@ -0,0 +1,10 @@
+#![no_std]
+
+pub mod i_sep_41;
+mod test;
+pub use i_sep_41::*;
+pub mod contract_sep_41;
+// pub use contract_sep_41::*;
+mod errors;
+mod events;
+mod storage;
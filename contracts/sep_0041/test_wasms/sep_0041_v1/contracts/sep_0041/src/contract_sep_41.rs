@@ -0,0 +1,801 @@
+use crate::errors::Sep0041Error;
+use crate::events::{
+    emit_add_minter, emit_admin_proposed, emit_admin_renounced, emit_approve, emit_burn,
+    emit_clawback, emit_migrate, emit_mint, emit_paused, emit_remove_minter, emit_set_admin,
+    emit_set_authorized, emit_set_max_supply, emit_set_metadata, emit_set_pauser, emit_transfer,
+    emit_unpaused, emit_upgrade,
+};
+use crate::i_sep_41::ISep0041Admin;
+use crate::storage::{
+    AllowanaceDetails, DataKey, LegacyDataKey, MinterDetails, PendingAdminDetails, TokenMetadata,
+    BALANCE_BUMP_AMOUNT, BALANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT,
+    INSTANCE_LIFETIME_THRESHOLD, MAX_DECIMALS, MAX_NAME_LENGTH, MAX_SYMBOL_LENGTH, METADATA_KEY,
+    SCHEMA_VERSION,
+};
+use soroban_sdk::{
+    contract, contractimpl, log, panic_with_error, token::TokenInterface, Address, BytesN, Env,
+    String, Vec,
+};
+
+#[contract]
+pub struct Sep0041;
+
+#[contractimpl]
+impl Sep0041 {
+    pub fn __constructor(
+        env: &Env,
+        admin: Address,
+        decimal: u32,
+        name: String,
+        symbol: String,
+        auth_required: bool,
+        max_supply: Option<i128>,
+    ) {
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        Self::_update_metadata(
+            env,
+            &TokenMetadata {
+                decimal,
+                name,
+                symbol,
+            },
+        );
+        // when set, accounts start out deauthorized until the admin authorizes them
+        env.storage()
+            .instance()
+            .set(&DataKey::AuthRequired, &auth_required);
+        // no cap means the supply is only bounded by i128
+        if let Some(max_supply) = max_supply {
+            if max_supply <= 0 {
+                panic_with_error!(env, Sep0041Error::InvalidMaxSupply);
+            }
+            env.storage()
+                .instance()
+                .set(&DataKey::MaxSupply, &max_supply);
+        }
+        env.storage()
+            .instance()
+            .set(&DataKey::SchemaVersion, &SCHEMA_VERSION);
+        Self::_extend_instance_ttl(env);
+    }
+
+    pub fn total_supply(env: &Env) -> i128 {
+        Self::_extend_instance_ttl(env);
+        Self::_total_supply(env)
+    }
+
+    pub fn max_supply(env: &Env) -> Option<i128> {
+        Self::_extend_instance_ttl(env);
+        Self::_max_supply(env)
+    }
+}
+
+#[contractimpl]
+impl ISep0041Admin for Sep0041 {
+    fn mint(env: &Env, to: Address, amount: i128) {
+        Self::_extend_instance_ttl(env);
+        Self::_require_not_paused(env);
+        // only admin
+        let admin: Address = Self::_admin(env);
+        // throw error next
+        admin.require_auth();
+
+        Self::_check_for_zero_amount(env, amount);
+        Self::_mint(env, &to, amount);
+
+        emit_mint(env, admin, to, amount);
+    }
+
+    fn admin(env: &Env) -> Address {
+        Self::_extend_instance_ttl(env);
+        Self::_admin(env)
+    }
+
+    fn propose_admin(env: &Env, new_admin: Address, live_until_ledger: u32) {
+        Self::_extend_instance_ttl(env);
+        let admin: Address = Self::_admin(env);
+        admin.require_auth();
+
+        if live_until_ledger < Self::_current_ledger(env) {
+            panic_with_error!(env, Sep0041Error::InvalidExpirationLedger);
+        }
+
+        // a new proposal replaces any earlier one
+        env.storage().instance().set(
+            &DataKey::PendingAdmin,
+            &PendingAdminDetails {
+                admin: new_admin.clone(),
+                live_until_ledger,
+            },
+        );
+
+        emit_admin_proposed(env, admin, new_admin, live_until_ledger);
+    }
+
+    fn accept_admin(env: &Env) {
+        Self::_extend_instance_ttl(env);
+        let pending: PendingAdminDetails = env
+            .storage()
+            .instance()
+            .get(&DataKey::PendingAdmin)
+            .unwrap_or_else(|| panic_with_error!(env, Sep0041Error::NoPendingAdmin));
+        pending.admin.require_auth();
+
+        if pending.live_until_ledger < Self::_current_ledger(env) {
+            panic_with_error!(env, Sep0041Error::PendingAdminExpired);
+        }
+
+        let admin: Address = Self::_admin(env);
+        env.storage()
+            .instance()
+            .set(&DataKey::Admin, &pending.admin);
+        env.storage().instance().remove(&DataKey::PendingAdmin);
+
+        emit_set_admin(env, admin, pending.admin);
+    }
+
+    fn renounce_admin(env: &Env) {
+        Self::_extend_instance_ttl(env);
+        let admin: Address = Self::_admin(env);
+        admin.require_auth();
+
+        env.storage().instance().remove(&DataKey::Admin);
+        env.storage().instance().remove(&DataKey::PendingAdmin);
+
+        emit_admin_renounced(env, admin);
+    }
+
+    fn set_authorized(env: &Env, id: Address, authorize: bool) {
+        Self::_extend_instance_ttl(env);
+        let admin: Address = Self::_admin(env);
+        admin.require_auth();
+
+        let authorized_key: DataKey = DataKey::Authorized(id.clone());
+        env.storage().persistent().set(&authorized_key, &authorize);
+        env.storage().persistent().extend_ttl(
+            &authorized_key,
+            BALANCE_LIFETIME_THRESHOLD,
+            BALANCE_BUMP_AMOUNT,
+        );
+
+        emit_set_authorized(env, admin, id, authorize);
+    }
+
+    fn authorized(env: &Env, id: Address) -> bool {
+        Self::_extend_instance_ttl(env);
+        Self::_authorized(env, &id)
+    }
+
+    fn clawback(env: &Env, from: Address, amount: i128) {
+        Self::_extend_instance_ttl(env);
+        let admin: Address = Self::_admin(env);
+        admin.require_auth();
+
+        Self::_check_for_zero_amount(env, amount);
+        Self::_debit(env, &from, amount);
+
+        emit_clawback(env, admin, from, amount);
+    }
+
+    fn set_pauser(env: &Env, pauser: Address) {
+        Self::_extend_instance_ttl(env);
+        let admin: Address = Self::_admin(env);
+        admin.require_auth();
+
+        env.storage().instance().set(&DataKey::Pauser, &pauser);
+
+        emit_set_pauser(env, admin, pauser);
+    }
+
+    fn pause(env: &Env, caller: Address) {
+        Self::_extend_instance_ttl(env);
+        Self::_require_admin_or_pauser(env, &caller);
+
+        env.storage().instance().set(&DataKey::Paused, &true);
+
+        emit_paused(env, caller);
+    }
+
+    fn unpause(env: &Env, caller: Address) {
+        Self::_extend_instance_ttl(env);
+        Self::_require_admin_or_pauser(env, &caller);
+
+        env.storage().instance().set(&DataKey::Paused, &false);
+
+        emit_unpaused(env, caller);
+    }
+
+    fn paused(env: &Env) -> bool {
+        Self::_extend_instance_ttl(env);
+        Self::_paused(env)
+    }
+
+    fn set_metadata(env: &Env, name: String, symbol: String) {
+        Self::_extend_instance_ttl(env);
+        let admin: Address = Self::_admin(env);
+        admin.require_auth();
+
+        let metadata: TokenMetadata = TokenMetadata {
+            decimal: Self::_metadata(env).decimal,
+            name: name.clone(),
+            symbol: symbol.clone(),
+        };
+        Self::_update_metadata(env, &metadata);
+
+        emit_set_metadata(env, admin, name, symbol);
+    }
+
+    fn add_minter(env: &Env, minter: Address, quota: i128, period_ledgers: u32) {
+        Self::_extend_instance_ttl(env);
+        let admin: Address = Self::_admin(env);
+        admin.require_auth();
+
+        if quota <= 0 || period_ledgers == 0 {
+            panic_with_error!(env, Sep0041Error::InvalidMinterConfig);
+        }
+        let minter_details: MinterDetails = MinterDetails {
+            quota,
+            period_ledgers,
+            period_start: Self::_current_ledger(env),
+            minted_in_period: 0,
+        };
+        Self::_update_minter(env, &minter, &minter_details);
+
+        emit_add_minter(env, admin, minter, quota, period_ledgers);
+    }
+
+    fn remove_minter(env: &Env, minter: Address) {
+        Self::_extend_instance_ttl(env);
+        let admin: Address = Self::_admin(env);
+        admin.require_auth();
+
+        env.storage()
+            .persistent()
+            .remove(&DataKey::Minter(minter.clone()));
+
+        emit_remove_minter(env, admin, minter);
+    }
+
+    fn is_minter(env: &Env, id: Address) -> bool {
+        Self::_extend_instance_ttl(env);
+        Self::_minter(env, &id).is_some()
+    }
+
+    fn minter_mint(env: &Env, minter: Address, to: Address, amount: i128) {
+        Self::_extend_instance_ttl(env);
+        Self::_require_not_paused(env);
+        minter.require_auth();
+
+        Self::_check_for_zero_amount(env, amount);
+        let mut minter_details: MinterDetails = Self::_minter(env, &minter)
+            .unwrap_or_else(|| panic_with_error!(env, Sep0041Error::NotMinter));
+
+        // start a new period once the current one has run its course
+        let current_ledger: u32 = Self::_current_ledger(env);
+        let elapsed: u32 = current_ledger - minter_details.period_start;
+        if elapsed >= minter_details.period_ledgers {
+            minter_details.period_start =
+                current_ledger - (elapsed % minter_details.period_ledgers);
+            minter_details.minted_in_period = 0;
+        }
+
+        let minted_in_period: i128 =
+            Self::_checked_add(env, minter_details.minted_in_period, amount);
+        if minted_in_period > minter_details.quota {
+            panic_with_error!(env, Sep0041Error::MinterQuotaExceeded);
+        }
+        minter_details.minted_in_period = minted_in_period;
+        Self::_update_minter(env, &minter, &minter_details);
+
+        Self::_mint(env, &to, amount);
+
+        emit_mint(env, minter, to, amount);
+    }
+
+    fn upgrade(env: &Env, new_wasm_hash: BytesN<32>) {
+        Self::_extend_instance_ttl(env);
+        let admin: Address = Self::_admin(env);
+        admin.require_auth();
+
+        env.deployer()
+            .update_current_contract_wasm(new_wasm_hash.clone());
+
+        emit_upgrade(env, admin, new_wasm_hash);
+    }
+
+    fn schema_version(env: &Env) -> u32 {
+        Self::_extend_instance_ttl(env);
+        Self::_schema_version(env)
+    }
+
+    fn migrate(env: &Env, holders: Vec<Address>) {
+        Self::_extend_instance_ttl(env);
+        let admin: Address = Self::_admin(env);
+        admin.require_auth();
+
+        let from_version: u32 = Self::_schema_version(env);
+        if from_version < 1 {
+            Self::_migrate_legacy_metadata(env);
+        }
+        // balances can be moved in batches, this is a no-op for holders already migrated
+        for holder in holders.iter() {
+            Self::_migrate_legacy_balance(env, &holder);
+        }
+        env.storage()
+            .instance()
+            .set(&DataKey::SchemaVersion, &SCHEMA_VERSION);
+
+        emit_migrate(env, admin, from_version, SCHEMA_VERSION);
+    }
+
+    fn set_max_supply(env: &Env, max_supply: i128) {
+        Self::_extend_instance_ttl(env);
+        let admin: Address = Self::_admin(env);
+        admin.require_auth();
+
+        let raises_cap: bool = match Self::_max_supply(env) {
+            Some(current) => max_supply > current,
+            None => false,
+        };
+        if raises_cap || max_supply < Self::_total_supply(env) || max_supply <= 0 {
+            panic_with_error!(env, Sep0041Error::InvalidMaxSupply);
+        }
+        env.storage()
+            .instance()
+            .set(&DataKey::MaxSupply, &max_supply);
+
+        emit_set_max_supply(env, admin, max_supply);
+    }
+}
+
+#[contractimpl]
+impl TokenInterface for Sep0041 {
+    fn balance(env: Env, id: Address) -> i128 {
+        Self::_extend_instance_ttl(&env);
+        // get the balance from the storage
+        Self::_balance(&env, &id)
+    }
+
+    fn name(env: Env) -> String {
+        Self::_extend_instance_ttl(&env);
+        Self::_name(&env)
+    }
+    fn decimals(env: Env) -> u32 {
+        Self::_extend_instance_ttl(&env);
+        Self::_decimal(&env)
+    }
+    fn symbol(env: Env) -> String {
+        Self::_extend_instance_ttl(&env);
+        Self::_symbol(&env)
+    }
+
+    fn allowance(env: Env, from: Address, spender: Address) -> i128 {
+        Self::_extend_instance_ttl(&env);
+        let (amount, _) = Self::_allowance(&env, &from, &spender);
+        amount
+    }
+
+    fn approve(env: Env, from: Address, spender: Address, amount: i128, expiration_ledger: u32) {
+        Self::_extend_instance_ttl(&env);
+        Self::_require_not_paused(&env);
+        from.require_auth();
+
+        Self::_check_for_zero_amount(&env, amount);
+        // an allowance can only be set to expire in the past when it is being cleared
+        if amount != 0 && expiration_ledger < Self::_current_ledger(&env) {
+            panic_with_error!(&env, Sep0041Error::InvalidExpirationLedger);
+        }
+
+        //now create the details and save
+        let tx_details: AllowanaceDetails =
+            Self::_create_allowance_details(amount, expiration_ledger);
+        Self::_update_allowance(&env, from.clone(), spender.clone(), tx_details);
+
+        emit_approve(&env, from, spender, amount, expiration_ledger);
+    }
+
+    fn transfer(env: Env, from: Address, to: Address, amount: i128) {
+        Self::_extend_instance_ttl(&env);
+        Self::_require_not_paused(&env);
+        from.require_auth();
+
+        Self::_check_for_zero_amount(&env, amount);
+        Self::_transfer(&env, &from, &to, amount);
+    }
+
+    fn burn(env: Env, from: Address, amount: i128) {
+        Self::_extend_instance_ttl(&env);
+        Self::_require_not_paused(&env);
+        from.require_auth();
+
+        Self::_check_for_zero_amount(&env, amount);
+        Self::_burn(&env, &from, amount);
+    }
+
+    fn transfer_from(env: Env, spender: Address, from: Address, to: Address, amount: i128) {
+        Self::_extend_instance_ttl(&env);
+        Self::_require_not_paused(&env);
+        spender.require_auth();
+
+        Self::_check_for_zero_amount(&env, amount);
+        // check and update allowance
+        Self::_spend_allowance(&env, &from, &spender, amount);
+        // transfer
+        Self::_transfer(&env, &from, &to, amount);
+    }
+
+    fn burn_from(env: Env, spender: Address, from: Address, amount: i128) {
+        Self::_extend_instance_ttl(&env);
+        Self::_require_not_paused(&env);
+        spender.require_auth();
+
+        Self::_check_for_zero_amount(&env, amount);
+        Self::_spend_allowance(&env, &from, &spender, amount);
+        Self::_burn(&env, &from, amount);
+    }
+}
+
+impl Sep0041 {
+    fn _check_for_zero_amount(env: &Env, amount: i128) {
+        if amount <= 0 {
+            panic_with_error!(env, Sep0041Error::InvalidAmount);
+        }
+    }
+    fn _checked_add(env: &Env, a: i128, b: i128) -> i128 {
+        a.checked_add(b)
+            .unwrap_or_else(|| panic_with_error!(env, Sep0041Error::Overflow))
+    }
+    fn _checked_sub(env: &Env, a: i128, b: i128) -> i128 {
+        a.checked_sub(b)
+            .unwrap_or_else(|| panic_with_error!(env, Sep0041Error::Overflow))
+    }
+    fn _current_ledger(env: &Env) -> u32 {
+        env.ledger().sequence()
+    }
+
+    fn _name(env: &Env) -> String {
+        Self::_metadata(env).name
+    }
+    fn _decimal(env: &Env) -> u32 {
+        Self::_metadata(env).decimal
+    }
+    fn _symbol(env: &Env) -> String {
+        Self::_metadata(env).symbol
+    }
+
+    fn _metadata(env: &Env) -> TokenMetadata {
+        env.storage()
+            .instance()
+            .get(&METADATA_KEY)
+            .unwrap_or_else(|| panic_with_error!(env, Sep0041Error::NotInitialized))
+    }
+
+    fn _update_metadata(env: &Env, metadata: &TokenMetadata) {
+        if metadata.decimal > MAX_DECIMALS {
+            panic_with_error!(env, Sep0041Error::InvalidDecimals);
+        }
+        // names allow spaces and a little punctuation, symbols are plain alphanumerics
+        if !Self::_is_valid_text(&metadata.name, MAX_NAME_LENGTH, b" -_.") {
+            panic_with_error!(env, Sep0041Error::InvalidName);
+        }
+        if !Self::_is_valid_text(&metadata.symbol, MAX_SYMBOL_LENGTH, b"") {
+            panic_with_error!(env, Sep0041Error::InvalidSymbol);
+        }
+        env.storage().instance().set(&METADATA_KEY, metadata);
+    }
+
+    fn _is_valid_text(text: &String, max_length: u32, extra_chars: &[u8]) -> bool {
+        let length: u32 = text.len();
+        if length == 0 || length > max_length {
+            return false;
+        }
+        let mut buffer = [0_u8; MAX_NAME_LENGTH as usize];
+        let bytes = &mut buffer[..length as usize];
+        text.copy_into_slice(bytes);
+        bytes
+            .iter()
+            .all(|byte| byte.is_ascii_alphanumeric() || extra_chars.contains(byte))
+    }
+
+    fn _admin(env: &Env) -> Address {
+        // the admin is only ever missing once it has been renounced
+        env.storage()
+            .instance()
+            .get(&DataKey::Admin)
+            .unwrap_or_else(|| panic_with_error!(env, Sep0041Error::NoAdmin))
+    }
+
+    fn _create_allowance_details(amount: i128, expiration_ledger: u32) -> AllowanaceDetails {
+        AllowanaceDetails {
+            amount,
+            expiration_ledger,
+        }
+    }
+
+    fn _mint(env: &Env, to: &Address, amount: i128) {
+        Self::_require_authorized(env, to);
+        log!(env, "before balance");
+
+        let to_balance = Self::_balance(env, to);
+
+        log!(env, "after balance");
+
+        let new_balance: i128 = Self::_checked_add(env, to_balance, amount);
+        let total_supply: i128 = Self::_checked_add(env, Self::_total_supply(env), amount);
+        if let Some(max_supply) = Self::_max_supply(env) {
+            if total_supply > max_supply {
+                panic_with_error!(env, Sep0041Error::MaxSupplyExceeded);
+            }
+        }
+        // save the new balance
+        Self::_update_balance(env, to, new_balance);
+        Self::_update_total_supply(env, total_supply);
+    }
+
+    fn _burn(env: &Env, from: &Address, amount: i128) {
+        Self::_require_authorized(env, from);
+        Self::_debit(env, from, amount);
+
+        emit_burn(env, from.clone(), amount);
+    }
+
+    // removes tokens from circulation without any authorization check
+    fn _debit(env: &Env, from: &Address, amount: i128) {
+        let from_balance: i128 = Self::_balance(env, from);
+        if from_balance < amount {
+            panic_with_error!(env, Sep0041Error::InsufficientBalance);
+        }
+
+        // we update the states, from balance and the total supply
+        let from_new_balance: i128 = from_balance - amount;
+
+        // call the update method
+        Self::_update_balance(env, from, from_new_balance);
+
+        // get total_supply
+        let total_supply: i128 = Self::_total_supply(env);
+        // update total supply
+        Self::_update_total_supply(env, Self::_checked_sub(env, total_supply, amount));
+    }
+
+    fn _transfer(env: &Env, from: &Address, to: &Address, amount: i128) {
+        Self::_require_authorized(env, from);
+        Self::_require_authorized(env, to);
+
+        let from_balance: i128 = Self::_balance(env, from);
+        if from_balance < amount {
+            panic_with_error!(env, Sep0041Error::InsufficientBalance);
+        }
+        Self::_update_balance(env, from, from_balance - amount);
+
+        // to balance, read after the debit so a transfer to self nets out
+        let to_balance: i128 = Self::_balance(env, to);
+        let to_new_balance: i128 = Self::_checked_add(env, to_balance, amount);
+        Self::_update_balance(env, to, to_new_balance);
+
+        emit_transfer(env, from.clone(), to.clone(), amount);
+    }
+
+    fn _balance(env: &Env, id: &Address) -> i128 {
+        let balance_key: DataKey = DataKey::Balance(id.clone());
+        // log!(env, "balance key {}", balance_key);
+        if let Some(result) = env.storage().persistent().get::<_, i128>(&balance_key) {
+            env.storage().persistent().extend_ttl(
+                &balance_key,
+                BALANCE_LIFETIME_THRESHOLD,
+                BALANCE_BUMP_AMOUNT,
+            );
+            log!(env, "we should see here  {}", result);
+            return result;
+        }
+        0
+    }
+
+    fn _update_balance(env: &Env, id: &Address, amount: i128) {
+        let balance_key: DataKey = DataKey::Balance(id.clone());
+        env.storage().persistent().set(&balance_key, &amount);
+        env.storage().persistent().extend_ttl(
+            &balance_key,
+            BALANCE_LIFETIME_THRESHOLD,
+            BALANCE_BUMP_AMOUNT,
+        );
+        log!(env, "updated balance")
+    }
+
+    fn _update_allowance(
+        env: &Env,
+        from: Address,
+        spender: Address,
+        tx_details: AllowanaceDetails,
+    ) {
+        let allowance_key: DataKey = DataKey::Allowance(from, spender);
+        env.storage().temporary().set(&allowance_key, &tx_details);
+        Self::_extend_allowance_ttl(env, &allowance_key, tx_details.expiration_ledger);
+        log!(env, "done")
+    }
+    fn _allowance(env: &Env, from: &Address, spender: &Address) -> (i128, u32) {
+        let allowance_key: DataKey = DataKey::Allowance(from.clone(), spender.clone());
+        // an allowance that was never set or whose entry has been archived reads as zero
+        let Some(tx_details) = env
+            .storage()
+            .temporary()
+            .get::<_, AllowanaceDetails>(&allowance_key)
+        else {
+            return (0, 0);
+        };
+        // the allowance is usable up to and including its expiration ledger
+        if tx_details.expiration_ledger < Self::_current_ledger(env) {
+            return (0, tx_details.expiration_ledger);
+        }
+        Self::_extend_allowance_ttl(env, &allowance_key, tx_details.expiration_ledger);
+        (tx_details.amount, tx_details.expiration_ledger)
+    }
+
+    fn _spend_allowance(env: &Env, from: &Address, spender: &Address, amount: i128) {
+        let Some(tx_details) = env
+            .storage()
+            .temporary()
+            .get::<_, AllowanaceDetails>(&DataKey::Allowance(from.clone(), spender.clone()))
+        else {
+            panic_with_error!(env, Sep0041Error::InsufficientAllowance);
+        };
+        if tx_details.expiration_ledger < Self::_current_ledger(env) {
+            panic_with_error!(env, Sep0041Error::AllowanceExpired);
+        }
+        if tx_details.amount < amount {
+            panic_with_error!(env, Sep0041Error::InsufficientAllowance);
+        }
+
+        let tx_details: AllowanaceDetails = Self::_create_allowance_details(
+            tx_details.amount - amount,
+            tx_details.expiration_ledger,
+        );
+        Self::_update_allowance(env, from.clone(), spender.clone(), tx_details);
+    }
+
+    // allowances are temporary entries that only need to live until they expire
+    fn _extend_allowance_ttl(env: &Env, allowance_key: &DataKey, expiration_ledger: u32) {
+        let current_ledger: u32 = Self::_current_ledger(env);
+        if expiration_ledger < current_ledger {
+            return;
+        }
+        let live_for: u32 = expiration_ledger - current_ledger;
+        env.storage()
+            .temporary()
+            .extend_ttl(allowance_key, live_for, live_for);
+    }
+
+    fn _extend_instance_ttl(env: &Env) {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+    }
+
+    fn _authorized(env: &Env, id: &Address) -> bool {
+        let authorized_key: DataKey = DataKey::Authorized(id.clone());
+        if let Some(authorized) = env.storage().persistent().get::<_, bool>(&authorized_key) {
+            env.storage().persistent().extend_ttl(
+                &authorized_key,
+                BALANCE_LIFETIME_THRESHOLD,
+                BALANCE_BUMP_AMOUNT,
+            );
+            return authorized;
+        }
+        // accounts the admin never touched follow the mode chosen at construction
+        let auth_required: bool = env
+            .storage()
+            .instance()
+            .get(&DataKey::AuthRequired)
+            .unwrap_or(false);
+        !auth_required
+    }
+
+    fn _require_authorized(env: &Env, id: &Address) {
+        if !Self::_authorized(env, id) {
+            panic_with_error!(env, Sep0041Error::Unauthorized);
+        }
+    }
+
+    fn _minter(env: &Env, id: &Address) -> Option<MinterDetails> {
+        let minter_key: DataKey = DataKey::Minter(id.clone());
+        let minter_details: Option<MinterDetails> = env.storage().persistent().get(&minter_key);
+        if minter_details.is_some() {
+            env.storage().persistent().extend_ttl(
+                &minter_key,
+                BALANCE_LIFETIME_THRESHOLD,
+                BALANCE_BUMP_AMOUNT,
+            );
+        }
+        minter_details
+    }
+
+    fn _update_minter(env: &Env, id: &Address, minter_details: &MinterDetails) {
+        let minter_key: DataKey = DataKey::Minter(id.clone());
+        env.storage().persistent().set(&minter_key, minter_details);
+        env.storage().persistent().extend_ttl(
+            &minter_key,
+            BALANCE_LIFETIME_THRESHOLD,
+            BALANCE_BUMP_AMOUNT,
+        );
+    }
+
+    fn _schema_version(env: &Env) -> u32 {
+        // deployments from before versioning was introduced have no version stored
+        env.storage()
+            .instance()
+            .get(&DataKey::SchemaVersion)
+            .unwrap_or(0)
+    }
+
+    fn _migrate_legacy_metadata(env: &Env) {
+        let instance = env.storage().instance();
+        let name: Option<String> = instance.get(&LegacyDataKey::Name);
+        let symbol: Option<String> = instance.get(&LegacyDataKey::Symbol);
+        let decimal: Option<u32> = instance.get(&LegacyDataKey::Decimal);
+        if let (Some(name), Some(symbol), Some(decimal)) = (name, symbol, decimal) {
+            // written as is, legacy metadata predates validation
+            instance.set(
+                &METADATA_KEY,
+                &TokenMetadata {
+                    decimal,
+                    name,
+                    symbol,
+                },
+            );
+            instance.remove(&LegacyDataKey::Name);
+            instance.remove(&LegacyDataKey::Symbol);
+            instance.remove(&LegacyDataKey::Decimal);
+        }
+    }
+
+    fn _migrate_legacy_balance(env: &Env, holder: &Address) {
+        let legacy_key: LegacyDataKey = LegacyDataKey::Balance(holder.clone());
+        if let Some(legacy_balance) = env.storage().instance().get::<_, i128>(&legacy_key) {
+            let balance: i128 =
+                Self::_checked_add(env, Self::_balance(env, holder), legacy_balance);
+            Self::_update_balance(env, holder, balance);
+            env.storage().instance().remove(&legacy_key);
+        }
+    }
+
+    fn _max_supply(env: &Env) -> Option<i128> {
+        env.storage().instance().get(&DataKey::MaxSupply)
+    }
+
+    fn _paused(env: &Env) -> bool {
+        env.storage()
+            .instance()
+            .get(&DataKey::Paused)
+            .unwrap_or(false)
+    }
+
+    fn _require_not_paused(env: &Env) {
+        if Self::_paused(env) {
+            panic_with_error!(env, Sep0041Error::Paused);
+        }
+    }
+
+    fn _require_admin_or_pauser(env: &Env, caller: &Address) {
+        caller.require_auth();
+        let is_admin: bool =
+            env.storage().instance().get::<_, Address>(&DataKey::Admin) == Some(caller.clone());
+        let is_pauser: bool =
+            env.storage().instance().get::<_, Address>(&DataKey::Pauser) == Some(caller.clone());
+        if !is_admin && !is_pauser {
+            panic_with_error!(env, Sep0041Error::Unauthorized);
+        }
+    }
+
+    fn _total_supply(env: &Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::TotalSupply)
+            .unwrap_or(0)
+    }
+
+    fn _update_total_supply(env: &Env, total_supply: i128) {
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalSupply, &(total_supply));
+    }
+}
@@ -0,0 +1,27 @@
+use soroban_sdk::contracterror;
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum Sep0041Error {
+    InsufficientBalance = 1,
+    InsufficientAllowance = 2,
+    AllowanceExpired = 3,
+    InvalidAmount = 4,
+    NotInitialized = 5,
+    Overflow = 6,
+    Unauthorized = 7,
+    InvalidExpirationLedger = 8,
+    NoAdmin = 9,
+    NoPendingAdmin = 10,
+    PendingAdminExpired = 11,
+    Paused = 12,
+    MaxSupplyExceeded = 13,
+    InvalidMaxSupply = 14,
+    InvalidDecimals = 15,
+    InvalidName = 16,
+    InvalidSymbol = 17,
+    NotMinter = 18,
+    MinterQuotaExceeded = 19,
+    InvalidMinterConfig = 20,
+}
@@ -0,0 +1,126 @@
+pub use self::sep_0041_events::{
+    emit_add_minter, emit_admin_proposed, emit_admin_renounced, emit_approve, emit_burn,
+    emit_clawback, emit_migrate, emit_mint, emit_paused, emit_remove_minter, emit_set_admin,
+    emit_set_authorized, emit_set_max_supply, emit_set_metadata, emit_set_pauser, emit_transfer,
+    emit_unpaused, emit_upgrade,
+};
+
+mod sep_0041_events {
+    use soroban_sdk::{symbol_short, Address, BytesN, Env, String, Symbol};
+
+    // topics and data follow the layout defined by SEP-41
+
+    pub fn emit_transfer(env: &Env, from: Address, to: Address, amount: i128) {
+        env.events()
+            .publish((symbol_short!("transfer"), from, to), amount);
+    }
+
+    pub fn emit_mint(env: &Env, admin: Address, to: Address, amount: i128) {
+        env.events()
+            .publish((symbol_short!("mint"), admin, to), amount);
+    }
+
+    pub fn emit_burn(env: &Env, from: Address, amount: i128) {
+        env.events().publish((symbol_short!("burn"), from), amount);
+    }
+
+    pub fn emit_approve(
+        env: &Env,
+        from: Address,
+        spender: Address,
+        amount: i128,
+        live_until_ledger: u32,
+    ) {
+        env.events().publish(
+            (symbol_short!("approve"), from, spender),
+            (amount, live_until_ledger),
+        );
+    }
+
+    pub fn emit_admin_proposed(
+        env: &Env,
+        admin: Address,
+        new_admin: Address,
+        live_until_ledger: u32,
+    ) {
+        env.events().publish(
+            (Symbol::new(env, "propose_admin"), admin),
+            (new_admin, live_until_ledger),
+        );
+    }
+
+    // same layout as the stellar asset contract `set_admin` event
+    pub fn emit_set_admin(env: &Env, admin: Address, new_admin: Address) {
+        env.events()
+            .publish((symbol_short!("set_admin"), admin), new_admin);
+    }
+
+    pub fn emit_set_authorized(env: &Env, admin: Address, id: Address, authorize: bool) {
+        env.events()
+            .publish((Symbol::new(env, "set_authorized"), admin, id), authorize);
+    }
+
+    pub fn emit_clawback(env: &Env, admin: Address, from: Address, amount: i128) {
+        env.events()
+            .publish((symbol_short!("clawback"), admin, from), amount);
+    }
+
+    pub fn emit_set_pauser(env: &Env, admin: Address, pauser: Address) {
+        env.events()
+            .publish((Symbol::new(env, "set_pauser"), admin), pauser);
+    }
+
+    pub fn emit_paused(env: &Env, caller: Address) {
+        env.events().publish((symbol_short!("paused"), caller), ());
+    }
+
+    pub fn emit_unpaused(env: &Env, caller: Address) {
+        env.events()
+            .publish((symbol_short!("unpaused"), caller), ());
+    }
+
+    pub fn emit_set_max_supply(env: &Env, admin: Address, max_supply: i128) {
+        env.events()
+            .publish((Symbol::new(env, "set_max_supply"), admin), max_supply);
+    }
+
+    pub fn emit_set_metadata(env: &Env, admin: Address, name: String, symbol: String) {
+        env.events()
+            .publish((Symbol::new(env, "set_metadata"), admin), (name, symbol));
+    }
+
+    pub fn emit_add_minter(
+        env: &Env,
+        admin: Address,
+        minter: Address,
+        quota: i128,
+        period_ledgers: u32,
+    ) {
+        env.events().publish(
+            (Symbol::new(env, "add_minter"), admin, minter),
+            (quota, period_ledgers),
+        );
+    }
+
+    pub fn emit_remove_minter(env: &Env, admin: Address, minter: Address) {
+        env.events()
+            .publish((Symbol::new(env, "remove_minter"), admin, minter), ());
+    }
+
+    pub fn emit_upgrade(env: &Env, admin: Address, new_wasm_hash: BytesN<32>) {
+        env.events()
+            .publish((symbol_short!("upgrade"), admin), new_wasm_hash);
+    }
+
+    pub fn emit_migrate(env: &Env, admin: Address, from_version: u32, to_version: u32) {
+        env.events().publish(
+            (symbol_short!("migrate"), admin),
+            (from_version, to_version),
+        );
+    }
+
+    pub fn emit_admin_renounced(env: &Env, admin: Address) {
+        env.events()
+            .publish((Symbol::new(env, "renounce_admin"), admin), ());
+    }
+}
@@ -0,0 +1,41 @@
+use soroban_sdk::{contractclient, Address, BytesN, Env, String, Vec};
+
+// the token surface itself is `soroban_sdk::token::TokenInterface`, this trait
+// holds the privileged operations in the style of `StellarAssetInterface`
+#[contractclient(name = "Sep0041AdminClient")]
+pub trait ISep0041Admin {
+    fn mint(env: &Env, to: Address, amount: i128);
+    fn admin(env: &Env) -> Address;
+    // the proposed admin has until `live_until_ledger` (inclusive) to accept
+    fn propose_admin(env: &Env, new_admin: Address, live_until_ledger: u32);
+    fn accept_admin(env: &Env);
+    // permanently removes the admin, nothing can be minted afterwards
+    fn renounce_admin(env: &Env);
+    // deauthorized accounts can neither send nor receive tokens
+    fn set_authorized(env: &Env, id: Address, authorize: bool);
+    fn authorized(env: &Env, id: Address) -> bool;
+    // burns from any account, authorized or not, without its consent
+    fn clawback(env: &Env, from: Address, amount: i128);
+    // the pauser can pause and unpause alongside the admin
+    fn set_pauser(env: &Env, pauser: Address);
+    // while paused every balance or allowance changing call fails, reads keep working
+    fn pause(env: &Env, caller: Address);
+    fn unpause(env: &Env, caller: Address);
+    fn paused(env: &Env) -> bool;
+    // the cap can only ever be lowered, and never below the current total supply
+    fn set_max_supply(env: &Env, max_supply: i128);
+    // decimals are fixed at construction, only the name and symbol can change
+    fn set_metadata(env: &Env, name: String, symbol: String);
+    // a minter can mint up to `quota` every `period_ledgers` ledgers, adding an
+    // existing minter replaces its quota and starts a fresh period
+    fn add_minter(env: &Env, minter: Address, quota: i128, period_ledgers: u32);
+    fn remove_minter(env: &Env, minter: Address);
+    fn is_minter(env: &Env, id: Address) -> bool;
+    fn minter_mint(env: &Env, minter: Address, to: Address, amount: i128);
+    // swaps the contract code, storage is left untouched until `migrate` runs
+    fn upgrade(env: &Env, new_wasm_hash: BytesN<32>);
+    fn schema_version(env: &Env) -> u32;
+    // brings storage written by older versions up to the current schema. Balances
+    // are moved per holder so large holder sets can be migrated over several calls
+    fn migrate(env: &Env, holders: Vec<Address>);
+}
@@ -0,0 +1,80 @@
+use soroban_sdk::{contracttype, symbol_short, Address, String, Symbol};
+
+// ledgers close roughly every five seconds
+pub const DAY_IN_LEDGERS: u32 = 17280;
+
+// the instance holds the token metadata and admin, every call keeps it alive for a week
+pub const INSTANCE_BUMP_AMOUNT: u32 = 7 * DAY_IN_LEDGERS;
+pub const INSTANCE_LIFETIME_THRESHOLD: u32 = INSTANCE_BUMP_AMOUNT - DAY_IN_LEDGERS;
+
+// balances are persistent entries, touching one keeps it alive for a month
+pub const BALANCE_BUMP_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
+pub const BALANCE_LIFETIME_THRESHOLD: u32 = BALANCE_BUMP_AMOUNT - DAY_IN_LEDGERS;
+
+// same key and layout as `soroban_token_sdk::metadata::TokenMetadata` so explorers can read it
+pub const METADATA_KEY: Symbol = symbol_short!("METADATA");
+
+// bumped whenever the storage layout changes, `migrate` brings older layouts up to it
+pub const SCHEMA_VERSION: u32 = 1;
+
+pub const MAX_DECIMALS: u32 = 18;
+pub const MAX_NAME_LENGTH: u32 = 32;
+pub const MAX_SYMBOL_LENGTH: u32 = 12;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TokenMetadata {
+    pub decimal: u32,
+    pub name: String,
+    pub symbol: String,
+}
+
+#[contracttype]
+#[derive(Debug)]
+pub struct AllowanaceDetails {
+    pub amount: i128,
+    pub expiration_ledger: u32,
+}
+
+#[contracttype]
+#[derive(Debug)]
+pub struct PendingAdminDetails {
+    pub admin: Address,
+    pub live_until_ledger: u32,
+}
+
+#[contracttype]
+#[derive(Debug)]
+pub struct MinterDetails {
+    pub quota: i128,
+    pub period_ledgers: u32,
+    pub period_start: u32,
+    pub minted_in_period: i128,
+}
+
+#[contracttype]
+#[derive(Debug)]
+pub enum DataKey {
+    Balance(Address),
+    Admin,
+    TotalSupply,
+    Allowance(Address, Address),
+    PendingAdmin,
+    AuthRequired,
+    Authorized(Address),
+    Pauser,
+    Paused,
+    MaxSupply,
+    Minter(Address),
+    SchemaVersion,
+}
+
+// keys of the original (schema version 0) layout, where everything lived in instance storage
+#[contracttype]
+#[derive(Debug)]
+pub enum LegacyDataKey {
+    Balance(Address),
+    Name,
+    Symbol,
+    Decimal,
+}
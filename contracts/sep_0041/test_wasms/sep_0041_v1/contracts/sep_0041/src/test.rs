@@ -0,0 +1,1399 @@
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod test {
+
+    use soroban_sdk::{
+        symbol_short,
+        testutils::{
+            storage::{Instance as _, Persistent as _, Temporary as _},
+            Address as _, Events, Ledger, MockAuth, MockAuthInvoke,
+        },
+        token::{StellarAssetClient, TokenClient},
+        vec, Address, Env, IntoVal, String, Symbol,
+    };
+
+    fn generate_addresses(env: &Env) -> (Address, Address, Address) {
+        let first_address: Address = Address::generate(env);
+        let second_address: Address = Address::generate(env);
+        let third_address: Address = Address::generate(env);
+
+        (first_address, second_address, third_address)
+    }
+
+    use crate::contract_sep_41::{Sep0041, Sep0041Client};
+
+    // the original instance storage layout, from before `upgrade` existed
+    mod sep_0041_v0 {
+        soroban_sdk::contractimport!(file = "test_wasms/sep_0041_v0.wasm");
+    }
+    // the first build with `upgrade` and `migrate`
+    mod sep_0041_v1 {
+        soroban_sdk::contractimport!(file = "test_wasms/sep_0041_v1.wasm");
+    }
+    use crate::errors::Sep0041Error;
+    use crate::i_sep_41::Sep0041AdminClient;
+    use crate::storage::{
+        DataKey, LegacyDataKey, TokenMetadata, BALANCE_BUMP_AMOUNT, DAY_IN_LEDGERS,
+        INSTANCE_LIFETIME_THRESHOLD, METADATA_KEY, SCHEMA_VERSION,
+    };
+    fn setup() -> (Env, Sep0041Client<'static>, Address, Address) {
+        setup_with_config(false, None)
+    }
+
+    fn setup_with_config(
+        auth_required: bool,
+        max_supply: Option<i128>,
+    ) -> (Env, Sep0041Client<'static>, Address, Address) {
+        let env: Env = Env::default();
+
+        let (admin, _, _) = generate_addresses(&env);
+        let contract_id = env.register(
+            Sep0041,
+            (
+                admin.clone(),
+                18_u32,
+                String::from_str(&env, "loaded"),
+                String::from_str(&env, "lsd"),
+                auth_required,
+                max_supply,
+            ),
+        );
+        let contract_client = Sep0041Client::new(&env, &contract_id);
+        env.mock_all_auths();
+        (env, contract_client, contract_id, admin)
+    }
+
+    #[test]
+    fn test_constructor_values() {
+        let (env, contract, _, _) = setup();
+        assert_eq!(contract.name(), String::from_str(&env, "loaded"));
+        assert_eq!(contract.symbol(), String::from_str(&env, "lsd"));
+        assert_eq!(contract.decimals(), 18_u32);
+    }
+
+    #[test]
+    fn test_mint_and_balance() {
+        let (env, contract_instance, _, _) = setup();
+        let (_, user1, _) = generate_addresses(&env);
+        assert_eq!(contract_instance.balance(&user1), 0);
+        contract_instance.mint(&user1, &500);
+
+        assert_eq!(contract_instance.balance(&user1), 500);
+        assert_eq!(contract_instance.total_supply(), 500);
+    }
+
+    #[test]
+    fn test_transfer() {
+        let (env, contract_instance, _, _) = setup();
+        let (_, user1, user2) = generate_addresses(&env);
+
+        contract_instance.mint(&user1, &300);
+        contract_instance.transfer(&user1, &user2, &100);
+
+        assert_eq!(contract_instance.balance(&user1), 200);
+        assert_eq!(contract_instance.balance(&user2), 100);
+    }
+
+    #[test]
+    fn test_approve_and_allowance() {
+        let (env, contract_instance, _, _) = setup();
+        let (_, owner, spender) = generate_addresses(&env);
+
+        contract_instance.mint(&owner, &400);
+        contract_instance.approve(&owner, &spender, &150, &5);
+
+        let allowance = contract_instance.allowance(&owner, &spender);
+        assert_eq!(allowance, 150);
+    }
+
+    #[test]
+    fn test_transfer_from_updates_allowance() {
+        let (env, contract_instance, _, _) = setup();
+        let (_, owner, spender) = generate_addresses(&env);
+        let (_, _, recipient) = generate_addresses(&env);
+
+        contract_instance.mint(&owner, &500);
+        contract_instance.approve(&owner, &spender, &200, &5);
+        contract_instance.transfer_from(&spender, &owner, &recipient, &150);
+
+        assert_eq!(contract_instance.balance(&owner), 350);
+        assert_eq!(contract_instance.balance(&recipient), 150);
+        assert_eq!(contract_instance.allowance(&owner, &spender), 50);
+    }
+
+    #[test]
+    fn test_burn_and_burn_from() {
+        let (env, contract_instance, _, _) = setup();
+        let (_, owner, spender) = generate_addresses(&env);
+
+        // Burn
+        contract_instance.mint(&owner, &300);
+        contract_instance.burn(&owner, &100);
+        assert_eq!(contract_instance.balance(&owner), 200);
+        assert_eq!(contract_instance.total_supply(), 200);
+
+        // Burn From
+        contract_instance.approve(&owner, &spender, &150, &5);
+        contract_instance.burn_from(&spender, &owner, &100);
+        assert_eq!(contract_instance.balance(&owner), 100);
+        assert_eq!(contract_instance.allowance(&owner, &spender), 50);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_transfer_insufficient_balance_fails() {
+        let (env, contract_instance, _, _) = setup();
+        let (_, user1, user2) = generate_addresses(&env);
+        contract_instance.transfer(&user1, &user2, &50);
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #4)")]
+    fn test_transfer_zero_amount_fails() {
+        let (env, contract_instance, _, _) = setup();
+        let (_, user1, user2) = generate_addresses(&env);
+        contract_instance.transfer(&user1, &user2, &0);
+    }
+
+    #[test]
+    fn test_multiple_transfers() {
+        let (env, contract_instance, _, _) = setup();
+        let (_, user1, user2) = generate_addresses(&env);
+
+        contract_instance.mint(&user1, &500);
+        contract_instance.transfer(&user1, &user2, &100);
+        contract_instance.transfer(&user1, &user2, &50);
+
+        assert_eq!(contract_instance.balance(&user1), 350);
+        assert_eq!(contract_instance.balance(&user2), 150);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_transfer_from_exceeds_allowance_fails() {
+        let (env, contract_instance, _, _) = setup();
+        let (_, owner, spender) = generate_addresses(&env);
+        let (_, _, recipient) = generate_addresses(&env);
+
+        contract_instance.mint(&owner, &500);
+        contract_instance.approve(&owner, &spender, &100, &5);
+        contract_instance.transfer_from(&spender, &owner, &recipient, &150);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_allowance_expiry_fails() {
+        let (env, contract_instance, _, _) = setup();
+        let (_, owner, spender) = generate_addresses(&env);
+        let (_, _, recipient) = generate_addresses(&env);
+
+        contract_instance.mint(&owner, &300);
+        contract_instance.approve(&owner, &spender, &100, &1);
+
+        // Simulate ledgers closing beyond the expiration ledger
+        env.ledger().set_sequence_number(2);
+
+        contract_instance.transfer_from(&spender, &owner, &recipient, &50);
+    }
+
+    #[test]
+    fn test_allowance_is_zero_after_expiration_ledger() {
+        let (env, contract_instance, _, _) = setup();
+        let (_, owner, spender) = generate_addresses(&env);
+
+        env.ledger().set_sequence_number(10);
+        contract_instance.approve(&owner, &spender, &100, &20);
+
+        env.ledger().set_sequence_number(20);
+        assert_eq!(contract_instance.allowance(&owner, &spender), 100);
+
+        env.ledger().set_sequence_number(21);
+        assert_eq!(contract_instance.allowance(&owner, &spender), 0);
+    }
+
+    #[test]
+    fn test_transfer_from_on_expiration_ledger() {
+        let (env, contract_instance, _, _) = setup();
+        let (_, owner, spender) = generate_addresses(&env);
+        let (_, _, recipient) = generate_addresses(&env);
+
+        contract_instance.mint(&owner, &300);
+        contract_instance.approve(&owner, &spender, &100, &5);
+
+        env.ledger().set_sequence_number(5);
+        contract_instance.transfer_from(&spender, &owner, &recipient, &60);
+
+        assert_eq!(contract_instance.balance(&recipient), 60);
+        assert_eq!(contract_instance.allowance(&owner, &spender), 40);
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #8)")]
+    fn test_approve_with_past_expiration_fails() {
+        let (env, contract_instance, _, _) = setup();
+        let (_, owner, spender) = generate_addresses(&env);
+
+        env.ledger().set_sequence_number(100);
+        contract_instance.approve(&owner, &spender, &100, &99);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_burn_from_after_expiration_fails() {
+        let (env, contract_instance, _, _) = setup();
+        let (_, owner, spender) = generate_addresses(&env);
+
+        contract_instance.mint(&owner, &300);
+        contract_instance.approve(&owner, &spender, &100, &5);
+
+        env.ledger().set_sequence_number(6);
+        contract_instance.burn_from(&spender, &owner, &50);
+    }
+
+    #[test]
+    fn test_mint_emits_event() {
+        let (env, contract_instance, contract_id, admin) = setup();
+        let (_, user1, _) = generate_addresses(&env);
+
+        contract_instance.mint(&user1, &500);
+
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("mint"), admin, user1).into_val(&env),
+                    500_i128.into_val(&env),
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_transfer_emits_event() {
+        let (env, contract_instance, contract_id, _) = setup();
+        let (_, user1, user2) = generate_addresses(&env);
+
+        contract_instance.mint(&user1, &300);
+        contract_instance.transfer(&user1, &user2, &100);
+
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("transfer"), user1, user2).into_val(&env),
+                    100_i128.into_val(&env),
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_approve_emits_event() {
+        let (env, contract_instance, contract_id, _) = setup();
+        let (_, owner, spender) = generate_addresses(&env);
+
+        contract_instance.approve(&owner, &spender, &150, &5);
+
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("approve"), owner, spender).into_val(&env),
+                    (150_i128, 5_u32).into_val(&env),
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_transfer_from_emits_event() {
+        let (env, contract_instance, contract_id, _) = setup();
+        let (_, owner, spender) = generate_addresses(&env);
+        let (_, _, recipient) = generate_addresses(&env);
+
+        contract_instance.mint(&owner, &500);
+        contract_instance.approve(&owner, &spender, &200, &5);
+        contract_instance.transfer_from(&spender, &owner, &recipient, &150);
+
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("transfer"), owner, recipient).into_val(&env),
+                    150_i128.into_val(&env),
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_burn_and_burn_from_emit_events() {
+        let (env, contract_instance, contract_id, _) = setup();
+        let (_, owner, spender) = generate_addresses(&env);
+
+        contract_instance.mint(&owner, &300);
+        contract_instance.burn(&owner, &100);
+
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("burn"), owner.clone()).into_val(&env),
+                    100_i128.into_val(&env),
+                ),
+            ]
+        );
+
+        contract_instance.approve(&owner, &spender, &150, &5);
+        contract_instance.burn_from(&spender, &owner, &50);
+
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("burn"), owner).into_val(&env),
+                    50_i128.into_val(&env),
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_balance_and_allowance_storage_ttl() {
+        let (env, contract_instance, contract_id, _) = setup();
+        let (_, owner, spender) = generate_addresses(&env);
+
+        env.ledger().set_sequence_number(100);
+        contract_instance.mint(&owner, &500);
+        contract_instance.approve(&owner, &spender, &200, &1_100);
+
+        env.as_contract(&contract_id, || {
+            assert!(env.storage().instance().get_ttl() >= INSTANCE_LIFETIME_THRESHOLD);
+            assert_eq!(
+                env.storage()
+                    .persistent()
+                    .get_ttl(&DataKey::Balance(owner.clone())),
+                BALANCE_BUMP_AMOUNT
+            );
+            assert_eq!(
+                env.storage()
+                    .temporary()
+                    .get_ttl(&DataKey::Allowance(owner.clone(), spender.clone())),
+                1_000
+            );
+            assert!(!env
+                .storage()
+                .instance()
+                .has(&DataKey::Balance(owner.clone())));
+        });
+    }
+
+    #[test]
+    fn test_touched_balance_outlives_archival_window() {
+        let (env, contract_instance, contract_id, _) = setup();
+        let (_, holder, _) = generate_addresses(&env);
+
+        contract_instance.mint(&holder, &500);
+
+        // read the balance every few days for well past a single bump window
+        let mut sequence: u32 = 0;
+        while sequence < 3 * BALANCE_BUMP_AMOUNT {
+            sequence += 2 * DAY_IN_LEDGERS;
+            env.ledger().set_sequence_number(sequence);
+            assert_eq!(contract_instance.balance(&holder), 500);
+        }
+
+        env.as_contract(&contract_id, || {
+            assert!(
+                env.storage()
+                    .persistent()
+                    .get_ttl(&DataKey::Balance(holder.clone()))
+                    > BALANCE_BUMP_AMOUNT - DAY_IN_LEDGERS
+            );
+        });
+    }
+
+    #[test]
+    fn test_expired_allowance_entry_reads_as_zero() {
+        let (env, contract_instance, _, _) = setup();
+        let (_, owner, spender) = generate_addresses(&env);
+
+        contract_instance.approve(&owner, &spender, &100, &50);
+
+        // the temporary entry is gone once its ttl runs out
+        env.ledger().set_sequence_number(10_000);
+        assert_eq!(contract_instance.allowance(&owner, &spender), 0);
+    }
+
+    #[test]
+    fn test_transfer_insufficient_balance_error() {
+        let (env, contract_instance, _, _) = setup();
+        let (_, user1, user2) = generate_addresses(&env);
+
+        contract_instance.mint(&user1, &40);
+        assert_eq!(
+            contract_instance.try_transfer(&user1, &user2, &50),
+            Err(Ok(Sep0041Error::InsufficientBalance.into()))
+        );
+        assert_eq!(
+            contract_instance.try_burn(&user1, &50),
+            Err(Ok(Sep0041Error::InsufficientBalance.into()))
+        );
+    }
+
+    #[test]
+    fn test_negative_amounts_rejected() {
+        let (env, contract_instance, _, _) = setup();
+        let (_, owner, spender) = generate_addresses(&env);
+
+        contract_instance.mint(&owner, &100);
+        contract_instance.approve(&owner, &spender, &100, &5);
+
+        assert_eq!(
+            contract_instance.try_burn(&owner, &-10),
+            Err(Ok(Sep0041Error::InvalidAmount.into()))
+        );
+        assert_eq!(
+            contract_instance.try_transfer_from(&spender, &owner, &spender, &-10),
+            Err(Ok(Sep0041Error::InvalidAmount.into()))
+        );
+        assert_eq!(
+            contract_instance.try_burn_from(&spender, &owner, &0),
+            Err(Ok(Sep0041Error::InvalidAmount.into()))
+        );
+        assert_eq!(
+            contract_instance.try_mint(&owner, &-1),
+            Err(Ok(Sep0041Error::InvalidAmount.into()))
+        );
+    }
+
+    #[test]
+    fn test_allowance_errors() {
+        let (env, contract_instance, _, _) = setup();
+        let (_, owner, spender) = generate_addresses(&env);
+        let (_, _, recipient) = generate_addresses(&env);
+
+        contract_instance.mint(&owner, &500);
+
+        // no allowance at all
+        assert_eq!(
+            contract_instance.try_transfer_from(&spender, &owner, &recipient, &10),
+            Err(Ok(Sep0041Error::InsufficientAllowance.into()))
+        );
+
+        contract_instance.approve(&owner, &spender, &100, &5);
+        assert_eq!(
+            contract_instance.try_burn_from(&spender, &owner, &101),
+            Err(Ok(Sep0041Error::InsufficientAllowance.into()))
+        );
+
+        env.ledger().set_sequence_number(6);
+        assert_eq!(
+            contract_instance.try_transfer_from(&spender, &owner, &recipient, &10),
+            Err(Ok(Sep0041Error::AllowanceExpired.into()))
+        );
+    }
+
+    #[test]
+    fn test_mint_overflow_error() {
+        let (env, contract_instance, _, _) = setup();
+        let (_, user1, user2) = generate_addresses(&env);
+
+        contract_instance.mint(&user1, &i128::MAX);
+        assert_eq!(
+            contract_instance.try_mint(&user2, &1),
+            Err(Ok(Sep0041Error::Overflow.into()))
+        );
+    }
+
+    #[test]
+    fn test_transfer_to_self_keeps_balance() {
+        let (env, contract_instance, _, _) = setup();
+        let (_, user1, _) = generate_addresses(&env);
+
+        contract_instance.mint(&user1, &100);
+        contract_instance.transfer(&user1, &user1, &60);
+
+        assert_eq!(contract_instance.balance(&user1), 100);
+        assert_eq!(contract_instance.total_supply(), 100);
+    }
+
+    // runs the same scenario against any contract exposing the standard token interface
+    fn exercise_token_interface(env: &Env, token: &TokenClient, holder: &Address) {
+        let (_, spender, recipient) = generate_addresses(env);
+
+        assert_eq!(token.balance(holder), 1_000);
+
+        token.transfer(holder, &recipient, &100);
+        token.approve(holder, &spender, &300, &100);
+        assert_eq!(token.allowance(holder, &spender), 300);
+
+        token.transfer_from(&spender, holder, &recipient, &200);
+        token.burn_from(&spender, holder, &50);
+        token.burn(&recipient, &25);
+
+        assert_eq!(token.balance(holder), 650);
+        assert_eq!(token.balance(&recipient), 275);
+        assert_eq!(token.allowance(holder, &spender), 50);
+    }
+
+    #[test]
+    fn test_token_client_conformance() {
+        let (env, _, contract_id, _) = setup();
+        let (_, holder, _) = generate_addresses(&env);
+
+        Sep0041AdminClient::new(&env, &contract_id).mint(&holder, &1_000);
+
+        let token = TokenClient::new(&env, &contract_id);
+        assert_eq!(token.name(), String::from_str(&env, "loaded"));
+        assert_eq!(token.symbol(), String::from_str(&env, "lsd"));
+        assert_eq!(token.decimals(), 18);
+        exercise_token_interface(&env, &token, &holder);
+
+        // the stellar asset contract behaves identically under the same calls
+        let (issuer, _, _) = generate_addresses(&env);
+        let sac = env.register_stellar_asset_contract_v2(issuer);
+        StellarAssetClient::new(&env, &sac.address()).mint(&holder, &1_000);
+        exercise_token_interface(&env, &TokenClient::new(&env, &sac.address()), &holder);
+    }
+
+    fn mock_auth(
+        env: &Env,
+        contract_id: &Address,
+        signer: &Address,
+        fn_name: &str,
+        args: soroban_sdk::Vec<soroban_sdk::Val>,
+    ) {
+        env.mock_auths(&[MockAuth {
+            address: signer,
+            invoke: &MockAuthInvoke {
+                contract: contract_id,
+                fn_name,
+                args,
+                sub_invokes: &[],
+            },
+        }]);
+    }
+
+    #[test]
+    fn test_two_step_admin_transfer() {
+        let (env, contract_instance, contract_id, admin) = setup();
+        let (_, new_admin, holder) = generate_addresses(&env);
+        assert_eq!(contract_instance.admin(), admin);
+
+        mock_auth(
+            &env,
+            &contract_id,
+            &admin,
+            "propose_admin",
+            (new_admin.clone(), 100_u32).into_val(&env),
+        );
+        contract_instance.propose_admin(&new_admin, &100);
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (Symbol::new(&env, "propose_admin"), admin.clone()).into_val(&env),
+                    (new_admin.clone(), 100_u32).into_val(&env),
+                ),
+            ]
+        );
+        // nothing changes until the proposal is accepted
+        assert_eq!(contract_instance.admin(), admin);
+
+        mock_auth(
+            &env,
+            &contract_id,
+            &new_admin,
+            "accept_admin",
+            ().into_val(&env),
+        );
+        contract_instance.accept_admin();
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("set_admin"), admin.clone()).into_val(&env),
+                    new_admin.into_val(&env),
+                ),
+            ]
+        );
+        assert_eq!(contract_instance.admin(), new_admin);
+
+        // the old admin can no longer mint, the new one can
+        mock_auth(
+            &env,
+            &contract_id,
+            &admin,
+            "mint",
+            (holder.clone(), 10_i128).into_val(&env),
+        );
+        assert!(contract_instance.try_mint(&holder, &10).is_err());
+
+        mock_auth(
+            &env,
+            &contract_id,
+            &new_admin,
+            "mint",
+            (holder.clone(), 10_i128).into_val(&env),
+        );
+        contract_instance.mint(&holder, &10);
+        assert_eq!(contract_instance.balance(&holder), 10);
+    }
+
+    #[test]
+    fn test_propose_admin_requires_admin_auth() {
+        let (env, contract_instance, contract_id, _) = setup();
+        let (_, new_admin, _) = generate_addresses(&env);
+
+        mock_auth(
+            &env,
+            &contract_id,
+            &new_admin,
+            "propose_admin",
+            (new_admin.clone(), 100_u32).into_val(&env),
+        );
+        assert!(contract_instance
+            .try_propose_admin(&new_admin, &100)
+            .is_err());
+    }
+
+    #[test]
+    fn test_accept_admin_requires_pending_admin_auth() {
+        let (env, contract_instance, contract_id, admin) = setup();
+        let (_, new_admin, intruder) = generate_addresses(&env);
+
+        assert_eq!(
+            contract_instance.try_accept_admin(),
+            Err(Ok(Sep0041Error::NoPendingAdmin.into()))
+        );
+
+        mock_auth(
+            &env,
+            &contract_id,
+            &admin,
+            "propose_admin",
+            (new_admin.clone(), 100_u32).into_val(&env),
+        );
+        contract_instance.propose_admin(&new_admin, &100);
+
+        mock_auth(
+            &env,
+            &contract_id,
+            &intruder,
+            "accept_admin",
+            ().into_val(&env),
+        );
+        assert!(contract_instance.try_accept_admin().is_err());
+        assert_eq!(contract_instance.admin(), admin);
+    }
+
+    #[test]
+    fn test_pending_admin_expires() {
+        let (env, contract_instance, contract_id, admin) = setup();
+        let (_, new_admin, _) = generate_addresses(&env);
+
+        mock_auth(
+            &env,
+            &contract_id,
+            &admin,
+            "propose_admin",
+            (new_admin.clone(), 10_u32).into_val(&env),
+        );
+        contract_instance.propose_admin(&new_admin, &10);
+
+        env.ledger().set_sequence_number(11);
+        mock_auth(
+            &env,
+            &contract_id,
+            &new_admin,
+            "accept_admin",
+            ().into_val(&env),
+        );
+        assert_eq!(
+            contract_instance.try_accept_admin(),
+            Err(Ok(Sep0041Error::PendingAdminExpired.into()))
+        );
+        assert_eq!(contract_instance.admin(), admin);
+    }
+
+    #[test]
+    fn test_renounce_admin() {
+        let (env, contract_instance, contract_id, admin) = setup();
+        let (_, new_admin, holder) = generate_addresses(&env);
+
+        mock_auth(
+            &env,
+            &contract_id,
+            &admin,
+            "propose_admin",
+            (new_admin.clone(), 100_u32).into_val(&env),
+        );
+        contract_instance.propose_admin(&new_admin, &100);
+
+        mock_auth(
+            &env,
+            &contract_id,
+            &admin,
+            "renounce_admin",
+            ().into_val(&env),
+        );
+        contract_instance.renounce_admin();
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (Symbol::new(&env, "renounce_admin"), admin).into_val(&env),
+                    ().into_val(&env),
+                ),
+            ]
+        );
+
+        assert_eq!(
+            contract_instance.try_admin(),
+            Err(Ok(Sep0041Error::NoAdmin.into()))
+        );
+        // renouncing also drops the outstanding proposal
+        assert_eq!(
+            contract_instance.try_accept_admin(),
+            Err(Ok(Sep0041Error::NoPendingAdmin.into()))
+        );
+        env.mock_all_auths();
+        assert_eq!(
+            contract_instance.try_mint(&holder, &10),
+            Err(Ok(Sep0041Error::NoAdmin.into()))
+        );
+    }
+
+    #[test]
+    fn test_deauthorized_account_cannot_send_or_receive() {
+        let (env, contract_instance, contract_id, admin) = setup();
+        let (_, user1, user2) = generate_addresses(&env);
+
+        contract_instance.mint(&user1, &300);
+        assert!(contract_instance.authorized(&user1));
+
+        contract_instance.set_authorized(&user1, &false);
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (Symbol::new(&env, "set_authorized"), admin, user1.clone()).into_val(&env),
+                    false.into_val(&env),
+                ),
+            ]
+        );
+        assert!(!contract_instance.authorized(&user1));
+
+        assert_eq!(
+            contract_instance.try_transfer(&user1, &user2, &100),
+            Err(Ok(Sep0041Error::Unauthorized.into()))
+        );
+        assert_eq!(
+            contract_instance.try_burn(&user1, &100),
+            Err(Ok(Sep0041Error::Unauthorized.into()))
+        );
+
+        contract_instance.mint(&user2, &100);
+        assert_eq!(
+            contract_instance.try_transfer(&user2, &user1, &50),
+            Err(Ok(Sep0041Error::Unauthorized.into()))
+        );
+        assert_eq!(
+            contract_instance.try_mint(&user1, &50),
+            Err(Ok(Sep0041Error::Unauthorized.into()))
+        );
+
+        contract_instance.set_authorized(&user1, &true);
+        contract_instance.transfer(&user1, &user2, &100);
+        assert_eq!(contract_instance.balance(&user2), 200);
+    }
+
+    #[test]
+    fn test_auth_required_by_default() {
+        let (env, contract_instance, _, _) = setup_with_config(true, None);
+        let (_, user1, _) = generate_addresses(&env);
+
+        assert!(!contract_instance.authorized(&user1));
+        assert_eq!(
+            contract_instance.try_mint(&user1, &100),
+            Err(Ok(Sep0041Error::Unauthorized.into()))
+        );
+
+        contract_instance.set_authorized(&user1, &true);
+        contract_instance.mint(&user1, &100);
+        assert_eq!(contract_instance.balance(&user1), 100);
+    }
+
+    #[test]
+    fn test_clawback() {
+        let (env, contract_instance, contract_id, admin) = setup();
+        let (_, user1, _) = generate_addresses(&env);
+
+        contract_instance.mint(&user1, &300);
+        contract_instance.set_authorized(&user1, &false);
+
+        // clawback works whether or not the holder is authorized
+        contract_instance.clawback(&user1, &120);
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("clawback"), admin.clone(), user1.clone()).into_val(&env),
+                    120_i128.into_val(&env),
+                ),
+            ]
+        );
+        assert_eq!(contract_instance.balance(&user1), 180);
+        assert_eq!(contract_instance.total_supply(), 180);
+
+        assert_eq!(
+            contract_instance.try_clawback(&user1, &500),
+            Err(Ok(Sep0041Error::InsufficientBalance.into()))
+        );
+
+        mock_auth(
+            &env,
+            &contract_id,
+            &user1,
+            "clawback",
+            (user1.clone(), 10_i128).into_val(&env),
+        );
+        assert!(contract_instance.try_clawback(&user1, &10).is_err());
+    }
+
+    #[test]
+    fn test_pause_blocks_balance_changes() {
+        let (env, contract_instance, contract_id, admin) = setup();
+        let (_, owner, spender) = generate_addresses(&env);
+
+        contract_instance.mint(&owner, &500);
+        contract_instance.approve(&owner, &spender, &200, &5);
+
+        assert!(!contract_instance.paused());
+        contract_instance.pause(&admin);
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("paused"), admin.clone()).into_val(&env),
+                    ().into_val(&env),
+                ),
+            ]
+        );
+        assert!(contract_instance.paused());
+
+        let paused = Err(Ok(Sep0041Error::Paused.into()));
+        assert_eq!(contract_instance.try_mint(&owner, &1), paused);
+        assert_eq!(contract_instance.try_transfer(&owner, &spender, &1), paused);
+        assert_eq!(
+            contract_instance.try_transfer_from(&spender, &owner, &spender, &1),
+            paused
+        );
+        assert_eq!(contract_instance.try_burn(&owner, &1), paused);
+        assert_eq!(
+            contract_instance.try_burn_from(&spender, &owner, &1),
+            paused
+        );
+        assert_eq!(
+            contract_instance.try_approve(&owner, &spender, &1, &5),
+            paused
+        );
+
+        // reads keep working
+        assert_eq!(contract_instance.balance(&owner), 500);
+        assert_eq!(contract_instance.allowance(&owner, &spender), 200);
+        assert_eq!(contract_instance.total_supply(), 500);
+
+        contract_instance.unpause(&admin);
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("unpaused"), admin).into_val(&env),
+                    ().into_val(&env),
+                ),
+            ]
+        );
+        contract_instance.transfer(&owner, &spender, &100);
+        assert_eq!(contract_instance.balance(&spender), 100);
+    }
+
+    #[test]
+    fn test_pauser_role() {
+        let (env, contract_instance, contract_id, _) = setup();
+        let (_, pauser, stranger) = generate_addresses(&env);
+
+        assert_eq!(
+            contract_instance.try_pause(&pauser),
+            Err(Ok(Sep0041Error::Unauthorized.into()))
+        );
+
+        contract_instance.set_pauser(&pauser);
+
+        mock_auth(
+            &env,
+            &contract_id,
+            &pauser,
+            "pause",
+            (pauser.clone(),).into_val(&env),
+        );
+        contract_instance.pause(&pauser);
+        assert!(contract_instance.paused());
+
+        // naming the pauser without its signature is not enough
+        mock_auth(
+            &env,
+            &contract_id,
+            &stranger,
+            "unpause",
+            (pauser.clone(),).into_val(&env),
+        );
+        assert!(contract_instance.try_unpause(&pauser).is_err());
+
+        mock_auth(
+            &env,
+            &contract_id,
+            &pauser,
+            "unpause",
+            (pauser.clone(),).into_val(&env),
+        );
+        contract_instance.unpause(&pauser);
+        assert!(!contract_instance.paused());
+    }
+
+    #[test]
+    fn test_mint_respects_max_supply() {
+        let (env, contract_instance, _, _) = setup_with_config(false, Some(1_000));
+        let (_, user1, user2) = generate_addresses(&env);
+
+        assert_eq!(contract_instance.max_supply(), Some(1_000));
+        contract_instance.mint(&user1, &600);
+        contract_instance.mint(&user2, &400);
+        assert_eq!(
+            contract_instance.try_mint(&user2, &1),
+            Err(Ok(Sep0041Error::MaxSupplyExceeded.into()))
+        );
+
+        // burning frees room under the cap again
+        contract_instance.burn(&user1, &100);
+        contract_instance.mint(&user2, &100);
+        assert_eq!(contract_instance.total_supply(), 1_000);
+    }
+
+    #[test]
+    fn test_max_supply_can_only_be_lowered() {
+        let (env, contract_instance, contract_id, admin) = setup_with_config(false, Some(1_000));
+        let (_, user1, _) = generate_addresses(&env);
+
+        contract_instance.mint(&user1, &500);
+
+        assert_eq!(
+            contract_instance.try_set_max_supply(&1_001),
+            Err(Ok(Sep0041Error::InvalidMaxSupply.into()))
+        );
+        assert_eq!(
+            contract_instance.try_set_max_supply(&499),
+            Err(Ok(Sep0041Error::InvalidMaxSupply.into()))
+        );
+
+        contract_instance.set_max_supply(&800);
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (Symbol::new(&env, "set_max_supply"), admin).into_val(&env),
+                    800_i128.into_val(&env),
+                ),
+            ]
+        );
+        assert_eq!(contract_instance.max_supply(), Some(800));
+        assert_eq!(
+            contract_instance.try_mint(&user1, &301),
+            Err(Ok(Sep0041Error::MaxSupplyExceeded.into()))
+        );
+    }
+
+    #[test]
+    fn test_uncapped_supply_can_be_capped() {
+        let (env, contract_instance, _, _) = setup();
+        let (_, user1, _) = generate_addresses(&env);
+
+        assert_eq!(contract_instance.max_supply(), None);
+        contract_instance.mint(&user1, &500);
+        contract_instance.set_max_supply(&500);
+        assert_eq!(
+            contract_instance.try_mint(&user1, &1),
+            Err(Ok(Sep0041Error::MaxSupplyExceeded.into()))
+        );
+    }
+
+    #[test]
+    fn test_balance_overflow_error() {
+        let (env, contract_instance, _, _) = setup();
+        let (_, user1, user2) = generate_addresses(&env);
+
+        contract_instance.mint(&user1, &(i128::MAX - 10));
+        assert_eq!(
+            contract_instance.try_mint(&user1, &11),
+            Err(Ok(Sep0041Error::Overflow.into()))
+        );
+        contract_instance.mint(&user2, &10);
+        assert_eq!(contract_instance.total_supply(), i128::MAX);
+    }
+
+    fn register_with_metadata(env: &Env, decimal: u32, name: &str, symbol: &str) -> Address {
+        let (admin, _, _) = generate_addresses(env);
+        env.register(
+            Sep0041,
+            (
+                admin,
+                decimal,
+                String::from_str(env, name),
+                String::from_str(env, symbol),
+                false,
+                None::<i128>,
+            ),
+        )
+    }
+
+    #[test]
+    fn test_configurable_decimals_and_metadata_layout() {
+        let env: Env = Env::default();
+        let contract_id = register_with_metadata(&env, 7, "Bridged USD Coin", "USDC");
+        let contract_instance = Sep0041Client::new(&env, &contract_id);
+
+        assert_eq!(contract_instance.decimals(), 7);
+        assert_eq!(
+            contract_instance.name(),
+            String::from_str(&env, "Bridged USD Coin")
+        );
+
+        // explorers read the same key the soroban-token-sdk writes
+        let metadata: TokenMetadata = env.as_contract(&contract_id, || {
+            env.storage().instance().get(&METADATA_KEY).unwrap()
+        });
+        assert_eq!(
+            metadata,
+            TokenMetadata {
+                decimal: 7,
+                name: String::from_str(&env, "Bridged USD Coin"),
+                symbol: String::from_str(&env, "USDC"),
+            }
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #15)")]
+    fn test_constructor_rejects_too_many_decimals() {
+        let env: Env = Env::default();
+        register_with_metadata(&env, 19, "loaded", "lsd");
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #16)")]
+    fn test_constructor_rejects_empty_name() {
+        let env: Env = Env::default();
+        register_with_metadata(&env, 7, "", "lsd");
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #17)")]
+    fn test_constructor_rejects_invalid_symbol() {
+        let env: Env = Env::default();
+        register_with_metadata(&env, 7, "loaded", "l$d");
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #17)")]
+    fn test_constructor_rejects_long_symbol() {
+        let env: Env = Env::default();
+        register_with_metadata(&env, 7, "loaded", "ABCDEFGHIJKLM");
+    }
+
+    #[test]
+    fn test_set_metadata() {
+        let (env, contract_instance, contract_id, admin) = setup();
+        let name = String::from_str(&env, "loaded v2");
+        let symbol = String::from_str(&env, "LSD2");
+
+        contract_instance.set_metadata(&name, &symbol);
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (Symbol::new(&env, "set_metadata"), admin).into_val(&env),
+                    (name.clone(), symbol.clone()).into_val(&env),
+                ),
+            ]
+        );
+        assert_eq!(contract_instance.name(), name);
+        assert_eq!(contract_instance.symbol(), symbol);
+        assert_eq!(contract_instance.decimals(), 18);
+
+        assert_eq!(
+            contract_instance.try_set_metadata(&String::from_str(&env, "loaded\n"), &symbol),
+            Err(Ok(Sep0041Error::InvalidName.into()))
+        );
+
+        let (_, stranger, _) = generate_addresses(&env);
+        mock_auth(
+            &env,
+            &contract_id,
+            &stranger,
+            "set_metadata",
+            (name.clone(), symbol.clone()).into_val(&env),
+        );
+        assert!(contract_instance.try_set_metadata(&name, &symbol).is_err());
+    }
+
+    #[test]
+    fn test_minter_quota_per_period() {
+        let (env, contract_instance, contract_id, _) = setup();
+        let (_, minter, employee) = generate_addresses(&env);
+
+        env.ledger().set_sequence_number(100);
+        contract_instance.add_minter(&minter, &1_000, &50);
+        assert!(contract_instance.is_minter(&minter));
+
+        mock_auth(
+            &env,
+            &contract_id,
+            &minter,
+            "minter_mint",
+            (minter.clone(), employee.clone(), 600_i128).into_val(&env),
+        );
+        contract_instance.minter_mint(&minter, &employee, &600);
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("mint"), minter.clone(), employee.clone()).into_val(&env),
+                    600_i128.into_val(&env),
+                ),
+            ]
+        );
+
+        env.mock_all_auths();
+        assert_eq!(
+            contract_instance.try_minter_mint(&minter, &employee, &401),
+            Err(Ok(Sep0041Error::MinterQuotaExceeded.into()))
+        );
+        contract_instance.minter_mint(&minter, &employee, &400);
+
+        // the quota is restored once the period rolls over
+        env.ledger().set_sequence_number(149);
+        assert_eq!(
+            contract_instance.try_minter_mint(&minter, &employee, &1),
+            Err(Ok(Sep0041Error::MinterQuotaExceeded.into()))
+        );
+        env.ledger().set_sequence_number(150);
+        contract_instance.minter_mint(&minter, &employee, &1_000);
+
+        assert_eq!(contract_instance.balance(&employee), 2_000);
+        assert_eq!(contract_instance.total_supply(), 2_000);
+    }
+
+    #[test]
+    fn test_minter_mint_requires_minter_auth() {
+        let (env, contract_instance, contract_id, _) = setup();
+        let (_, minter, stranger) = generate_addresses(&env);
+
+        contract_instance.add_minter(&minter, &1_000, &50);
+
+        mock_auth(
+            &env,
+            &contract_id,
+            &stranger,
+            "minter_mint",
+            (minter.clone(), stranger.clone(), 10_i128).into_val(&env),
+        );
+        assert!(contract_instance
+            .try_minter_mint(&minter, &stranger, &10)
+            .is_err());
+
+        env.mock_all_auths();
+        assert_eq!(
+            contract_instance.try_minter_mint(&stranger, &stranger, &10),
+            Err(Ok(Sep0041Error::NotMinter.into()))
+        );
+    }
+
+    #[test]
+    fn test_remove_minter() {
+        let (env, contract_instance, contract_id, admin) = setup();
+        let (_, minter, employee) = generate_addresses(&env);
+
+        assert_eq!(
+            contract_instance.try_add_minter(&minter, &0, &50),
+            Err(Ok(Sep0041Error::InvalidMinterConfig.into()))
+        );
+        contract_instance.add_minter(&minter, &1_000, &50);
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (
+                        Symbol::new(&env, "add_minter"),
+                        admin.clone(),
+                        minter.clone()
+                    )
+                        .into_val(&env),
+                    (1_000_i128, 50_u32).into_val(&env),
+                ),
+            ]
+        );
+
+        contract_instance.remove_minter(&minter);
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (Symbol::new(&env, "remove_minter"), admin, minter.clone()).into_val(&env),
+                    ().into_val(&env),
+                ),
+            ]
+        );
+        assert!(!contract_instance.is_minter(&minter));
+        assert_eq!(
+            contract_instance.try_minter_mint(&minter, &employee, &10),
+            Err(Ok(Sep0041Error::NotMinter.into()))
+        );
+    }
+
+    #[test]
+    fn test_upgrade_to_uploaded_wasm() {
+        let (env, contract_instance, contract_id, admin) = setup();
+        let (_, holder, recipient) = generate_addresses(&env);
+
+        contract_instance.mint(&holder, &500);
+        assert_eq!(contract_instance.schema_version(), SCHEMA_VERSION);
+
+        let new_wasm_hash = env.deployer().upload_contract_wasm(sep_0041_v1::WASM);
+
+        mock_auth(
+            &env,
+            &contract_id,
+            &holder,
+            "upgrade",
+            (new_wasm_hash.clone(),).into_val(&env),
+        );
+        assert!(contract_instance.try_upgrade(&new_wasm_hash).is_err());
+
+        mock_auth(
+            &env,
+            &contract_id,
+            &admin,
+            "upgrade",
+            (new_wasm_hash.clone(),).into_val(&env),
+        );
+        contract_instance.upgrade(&new_wasm_hash);
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("upgrade"), admin).into_val(&env),
+                    new_wasm_hash.into_val(&env),
+                ),
+            ]
+        );
+
+        // state survives and the uploaded code now serves the calls
+        let upgraded = sep_0041_v1::Client::new(&env, &contract_id);
+        env.mock_all_auths();
+        assert_eq!(upgraded.balance(&holder), 500);
+        upgraded.transfer(&holder, &recipient, &200);
+        assert_eq!(upgraded.balance(&recipient), 200);
+        assert_eq!(upgraded.schema_version(), 1);
+    }
+
+    #[test]
+    fn test_migrate_legacy_instance_layout() {
+        let env: Env = Env::default();
+        env.mock_all_auths();
+        let (admin, holder, other_holder) = generate_addresses(&env);
+
+        let contract_id = env.register(
+            sep_0041_v0::WASM,
+            (
+                admin.clone(),
+                String::from_str(&env, "loaded"),
+                String::from_str(&env, "lsd"),
+            ),
+        );
+        let legacy = sep_0041_v0::Client::new(&env, &contract_id);
+        legacy.mint(&holder, &500);
+        legacy.mint(&other_holder, &300);
+
+        // legacy deployments predate `upgrade`, so swap the code from inside the contract
+        let new_wasm_hash = env.deployer().upload_contract_wasm(sep_0041_v1::WASM);
+        env.as_contract(&contract_id, || {
+            env.deployer().update_current_contract_wasm(new_wasm_hash);
+        });
+
+        let contract_instance = Sep0041Client::new(&env, &contract_id);
+        assert_eq!(contract_instance.schema_version(), 0);
+
+        // holders can be migrated over several calls
+        contract_instance.migrate(&vec![&env, holder.clone()]);
+        assert_eq!(contract_instance.schema_version(), SCHEMA_VERSION);
+        assert_eq!(contract_instance.name(), String::from_str(&env, "loaded"));
+        assert_eq!(contract_instance.decimals(), 18);
+        assert_eq!(contract_instance.balance(&holder), 500);
+        assert_eq!(contract_instance.balance(&other_holder), 0);
+
+        contract_instance.migrate(&vec![&env, other_holder.clone(), holder.clone()]);
+        assert_eq!(contract_instance.balance(&other_holder), 300);
+        assert_eq!(contract_instance.balance(&holder), 500);
+        assert_eq!(contract_instance.total_supply(), 800);
+
+        env.as_contract(&contract_id, || {
+            assert!(!env
+                .storage()
+                .instance()
+                .has(&LegacyDataKey::Balance(holder.clone())));
+            assert!(env
+                .storage()
+                .persistent()
+                .has(&DataKey::Balance(holder.clone())));
+        });
+    }
+}
@@ -0,0 +1,178 @@
+#![no_std]
+use soroban_sdk::{
+    contract, contractclient, contracterror, contractimpl, contracttype, panic_with_error,
+    token::TokenClient, Address, Bytes, Env, Symbol,
+};
+
+mod test;
+
+// the instance holds the terms, every call keeps it alive for a week
+const DAY_IN_LEDGERS: u32 = 17280;
+const INSTANCE_BUMP_AMOUNT: u32 = 7 * DAY_IN_LEDGERS;
+const INSTANCE_LIFETIME_THRESHOLD: u32 = INSTANCE_BUMP_AMOUNT - DAY_IN_LEDGERS;
+
+// loan fees are in basis points of the loan, at most 10%
+const BPS_DENOMINATOR: i128 = 10_000;
+const MAX_FLASH_FEE_BPS: u32 = 1_000;
+
+#[contracttype]
+#[derive(Debug)]
+pub enum DataKey {
+    Admin,
+    Token,
+    FeeBps,
+    MaxAmount,
+}
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum FlashLenderError {
+    InvalidAmount = 1,
+    InvalidFee = 2,
+    LoanTooLarge = 3,
+    LoanNotRepaid = 4,
+}
+
+// the part of the SEP-41 admin interface the lender needs. Declared here rather than taken
+// from the token crate, which would link the whole token contract into the lender
+#[contractclient(name = "MinterClient")]
+pub trait IMinter {
+    fn minter_mint(env: &Env, minter: Address, to: Address, amount: i128);
+}
+
+// implemented by contracts borrowing from the lender. `amount` of `token` is minted to the
+// borrower before the hook runs and is its own to move until the hook returns. To repay, the
+// hook approves `lender` for `amount + fee`, returning false or failing reverts the loan
+#[contractclient(name = "FlashBorrowerClient")]
+pub trait IFlashBorrower {
+    fn on_flash_loan(
+        env: &Env,
+        lender: Address,
+        token: Address,
+        amount: i128,
+        fee: i128,
+        data: Bytes,
+    ) -> bool;
+}
+
+// lends freshly minted tokens of a SEP-41 token that made it a minter. The loan and its fee
+// are burned from the borrower afterwards, so the fee leaves the supply. The token is not on the call
+// stack while the hook runs, so the borrower can call it like any other holder. Loans count
+// against the minter quota, which bounds what can be lent every period
+#[contract]
+pub struct Sep0041FlashLender;
+
+#[contractimpl]
+impl Sep0041FlashLender {
+    pub fn __constructor(
+        env: &Env,
+        admin: Address,
+        token: Address,
+        fee_bps: u32,
+        max_amount: i128,
+    ) {
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        env.storage().instance().set(&DataKey::Token, &token);
+        Self::_set_terms(env, fee_bps, max_amount);
+        Self::_extend_instance_ttl(env);
+    }
+
+    // loans are charged `fee_bps` and capped at `max_amount`, 0 turns them off
+    pub fn set_terms(env: &Env, fee_bps: u32, max_amount: i128) {
+        Self::_extend_instance_ttl(env);
+        let admin: Address = Self::admin(env);
+        admin.require_auth();
+
+        Self::_set_terms(env, fee_bps, max_amount);
+        env.events().publish(
+            (Symbol::new(env, "set_terms"), admin),
+            (fee_bps, max_amount),
+        );
+    }
+
+    pub fn flash_loan(env: &Env, receiver: Address, amount: i128, data: Bytes) {
+        Self::_extend_instance_ttl(env);
+        // the borrower pays the fee, so nobody else can start a loan on its behalf
+        receiver.require_auth();
+
+        if amount <= 0 {
+            panic_with_error!(env, FlashLenderError::InvalidAmount);
+        }
+        if amount > Self::max_flash_loan(env) {
+            panic_with_error!(env, FlashLenderError::LoanTooLarge);
+        }
+        let fee: i128 = Self::flash_fee(env, amount);
+        let lender: Address = env.current_contract_address();
+        let token_address: Address = Self::token(env);
+
+        MinterClient::new(env, &token_address).minter_mint(&lender, &receiver, &amount);
+
+        let borrower: FlashBorrowerClient = FlashBorrowerClient::new(env, &receiver);
+        match borrower.try_on_flash_loan(&lender, &token_address, &amount, &fee, &data) {
+            Ok(Ok(true)) => {}
+            _ => panic_with_error!(env, FlashLenderError::LoanNotRepaid),
+        }
+
+        // out of the allowance the hook granted
+        let token: TokenClient = TokenClient::new(env, &token_address);
+        if token
+            .try_burn_from(&lender, &receiver, &(amount + fee))
+            .is_err()
+        {
+            panic_with_error!(env, FlashLenderError::LoanNotRepaid);
+        }
+
+        env.events()
+            .publish((Symbol::new(env, "flash_loan"), receiver), (amount, fee));
+    }
+
+    pub fn admin(env: &Env) -> Address {
+        Self::_extend_instance_ttl(env);
+        env.storage().instance().get(&DataKey::Admin).unwrap()
+    }
+
+    pub fn token(env: &Env) -> Address {
+        Self::_extend_instance_ttl(env);
+        env.storage().instance().get(&DataKey::Token).unwrap()
+    }
+
+    pub fn max_flash_loan(env: &Env) -> i128 {
+        Self::_extend_instance_ttl(env);
+        env.storage()
+            .instance()
+            .get(&DataKey::MaxAmount)
+            .unwrap_or(0)
+    }
+
+    // rounds up, unlike the transfer fee of the token, so a loan split into small ones still
+    // pays. Split up so large amounts cannot overflow
+    pub fn flash_fee(env: &Env, amount: i128) -> i128 {
+        Self::_extend_instance_ttl(env);
+        let fee_bps: i128 = env
+            .storage()
+            .instance()
+            .get::<_, u32>(&DataKey::FeeBps)
+            .unwrap_or(0) as i128;
+        let remainder: i128 = amount % BPS_DENOMINATOR * fee_bps;
+        amount / BPS_DENOMINATOR * fee_bps + (remainder + BPS_DENOMINATOR - 1) / BPS_DENOMINATOR
+    }
+
+    fn _set_terms(env: &Env, fee_bps: u32, max_amount: i128) {
+        if fee_bps > MAX_FLASH_FEE_BPS {
+            panic_with_error!(env, FlashLenderError::InvalidFee);
+        }
+        if max_amount < 0 {
+            panic_with_error!(env, FlashLenderError::InvalidAmount);
+        }
+        env.storage().instance().set(&DataKey::FeeBps, &fee_bps);
+        env.storage()
+            .instance()
+            .set(&DataKey::MaxAmount, &max_amount);
+    }
+
+    fn _extend_instance_ttl(env: &Env) {
+        env.storage()
+            .instance()
+            .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+    }
+}
@@ -0,0 +1,280 @@
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod test {
+    use crate::{
+        FlashLenderError, Sep0041FlashLender, Sep0041FlashLenderClient, DAY_IN_LEDGERS,
+        INSTANCE_BUMP_AMOUNT,
+    };
+    use sep_0041::contract_sep_41::{Sep0041, Sep0041Client};
+    use soroban_sdk::{
+        testutils::{storage::Instance as _, Address as _, Events, Ledger},
+        vec, Address, Bytes, Env, IntoVal, String, Symbol,
+    };
+
+    // trades the loan with a market that pays back `profit` on top, then repays when
+    // `repay` is set
+    mod trading_borrower {
+        use crate::IFlashBorrower;
+        use soroban_sdk::{
+            contract, contractimpl, symbol_short, token::TokenClient, Address, Bytes, Env,
+        };
+
+        #[contract]
+        pub struct TradingBorrower;
+
+        #[contractimpl]
+        impl TradingBorrower {
+            pub fn __constructor(env: &Env, market: Address, profit: i128, repay: bool) {
+                env.storage()
+                    .instance()
+                    .set(&symbol_short!("market"), &market);
+                env.storage()
+                    .instance()
+                    .set(&symbol_short!("profit"), &profit);
+                env.storage()
+                    .instance()
+                    .set(&symbol_short!("repay"), &repay);
+            }
+        }
+
+        #[contractimpl]
+        impl IFlashBorrower for TradingBorrower {
+            fn on_flash_loan(
+                env: &Env,
+                lender: Address,
+                token: Address,
+                amount: i128,
+                fee: i128,
+                data: Bytes,
+            ) -> bool {
+                let borrower: Address = env.current_contract_address();
+                let market: Address = env
+                    .storage()
+                    .instance()
+                    .get(&symbol_short!("market"))
+                    .unwrap();
+                let profit: i128 = env
+                    .storage()
+                    .instance()
+                    .get(&symbol_short!("profit"))
+                    .unwrap();
+                let token: TokenClient = TokenClient::new(env, &token);
+
+                token.transfer(&borrower, &market, &amount);
+                token.transfer_from(&borrower, &market, &borrower, &(amount + profit));
+
+                let repay: bool = env
+                    .storage()
+                    .instance()
+                    .get(&symbol_short!("repay"))
+                    .unwrap();
+                if repay {
+                    token.approve(
+                        &borrower,
+                        &lender,
+                        &(amount + fee),
+                        &env.ledger().sequence(),
+                    );
+                }
+                env.storage().instance().set(&symbol_short!("data"), &data);
+                true
+            }
+        }
+    }
+
+    // never agrees to repay
+    mod defaulting_borrower {
+        use crate::IFlashBorrower;
+        use soroban_sdk::{contract, contractimpl, Address, Bytes, Env};
+
+        #[contract]
+        pub struct DefaultingBorrower;
+
+        #[contractimpl]
+        impl IFlashBorrower for DefaultingBorrower {
+            fn on_flash_loan(
+                _env: &Env,
+                _lender: Address,
+                _token: Address,
+                _amount: i128,
+                _fee: i128,
+                _data: Bytes,
+            ) -> bool {
+                false
+            }
+        }
+    }
+
+    fn setup() -> (
+        Env,
+        Sep0041Client<'static>,
+        Sep0041FlashLenderClient<'static>,
+        Address,
+    ) {
+        let env: Env = Env::default();
+
+        let admin: Address = Address::generate(&env);
+        let token_id = env.register(
+            Sep0041,
+            (
+                admin.clone(),
+                18_u32,
+                String::from_str(&env, "loaded"),
+                String::from_str(&env, "lsd"),
+                false,
+                None::<i128>,
+                None::<Address>,
+            ),
+        );
+        let lender_id = env.register(
+            Sep0041FlashLender,
+            (admin.clone(), token_id.clone(), 50_u32, 1_000_000_i128),
+        );
+        env.mock_all_auths();
+
+        let token = Sep0041Client::new(&env, &token_id);
+        let lender = Sep0041FlashLenderClient::new(&env, &lender_id);
+        token.add_minter(&lender_id, &100_000, &100);
+
+        // a market holding some tokens, it lets the borrower take them back
+        let market: Address = Address::generate(&env);
+        token.mint(&market, &1000);
+
+        (env, token, lender, market)
+    }
+
+    fn register_borrower(env: &Env, market: &Address, profit: i128, repay: bool) -> Address {
+        env.register(
+            trading_borrower::TradingBorrower,
+            (market.clone(), profit, repay),
+        )
+    }
+
+    #[test]
+    fn test_flash_loan() {
+        let (env, token, lender, market) = setup();
+        let borrower_id = register_borrower(&env, &market, 40, true);
+        token.approve(&market, &borrower_id, &20_000, &100);
+        assert_eq!(lender.flash_fee(&10_000), 50);
+        // rounded up, a small loan still pays
+        assert_eq!(lender.flash_fee(&1), 1);
+
+        token.mint(&borrower_id, &80);
+        let data: Bytes = Bytes::from_slice(&env, b"arbitrage");
+        lender.flash_loan(&borrower_id, &10_000, &data);
+
+        // the token events of the loan come first
+        let events = env.events().all();
+        assert_eq!(
+            events.slice(events.len() - 1..),
+            vec![
+                &env,
+                (
+                    lender.address.clone(),
+                    (Symbol::new(&env, "flash_loan"), borrower_id.clone()).into_val(&env),
+                    (10_000_i128, 50_i128).into_val(&env),
+                ),
+            ]
+        );
+        env.as_contract(&borrower_id, || {
+            let stored: Bytes = env
+                .storage()
+                .instance()
+                .get(&soroban_sdk::symbol_short!("data"))
+                .unwrap();
+            assert_eq!(stored, data.clone());
+        });
+        // the borrowed tokens went to the market and came back with the profit
+        assert_eq!(token.balance(&market), 960);
+        assert_eq!(token.balance(&borrower_id), 80 + 40 - 50);
+        assert_eq!(token.allowance(&borrower_id, &lender.address), 0);
+        // the loan is burned again, the fee with it
+        assert_eq!(token.total_supply(), 1030);
+    }
+
+    #[test]
+    fn test_calls_extend_instance_ttl() {
+        let (env, _, lender, _) = setup();
+        env.ledger()
+            .set_sequence_number(env.ledger().sequence() + 3 * DAY_IN_LEDGERS);
+        env.as_contract(&lender.address, || {
+            assert!(env.storage().instance().get_ttl() < INSTANCE_BUMP_AMOUNT);
+        });
+
+        lender.max_flash_loan();
+        env.as_contract(&lender.address, || {
+            assert_eq!(env.storage().instance().get_ttl(), INSTANCE_BUMP_AMOUNT);
+        });
+    }
+
+    #[test]
+    fn test_flash_loan_reverts_when_not_repaid() {
+        let (env, token, lender, market) = setup();
+        let defaulting_id = env.register(defaulting_borrower::DefaultingBorrower, ());
+        token.mint(&defaulting_id, &100);
+
+        // refuses to repay
+        let res = lender.try_flash_loan(&defaulting_id, &10_000, &Bytes::new(&env));
+        assert_eq!(res, Err(Ok(FlashLenderError::LoanNotRepaid.into())));
+        assert_eq!(token.balance(&defaulting_id), 100);
+        assert_eq!(token.total_supply(), 1100);
+
+        // keeps the loan without approving the lender
+        let keeping_id = register_borrower(&env, &market, 0, false);
+        token.approve(&market, &keeping_id, &20_000, &100);
+        let res = lender.try_flash_loan(&keeping_id, &10_000, &Bytes::new(&env));
+        assert_eq!(res, Err(Ok(FlashLenderError::LoanNotRepaid.into())));
+        assert_eq!(token.balance(&keeping_id), 0);
+
+        // willing, but cannot cover the fee
+        let borrower_id = register_borrower(&env, &market, 0, true);
+        token.approve(&market, &borrower_id, &20_000, &100);
+        let res = lender.try_flash_loan(&borrower_id, &10_000, &Bytes::new(&env));
+        assert_eq!(res, Err(Ok(FlashLenderError::LoanNotRepaid.into())));
+        assert_eq!(token.balance(&borrower_id), 0);
+        assert_eq!(token.balance(&market), 1000);
+        assert_eq!(token.total_supply(), 1100);
+
+        // not a borrower at all
+        let not_a_borrower: Address = Address::generate(&env);
+        let res = lender.try_flash_loan(&not_a_borrower, &10_000, &Bytes::new(&env));
+        assert_eq!(res, Err(Ok(FlashLenderError::LoanNotRepaid.into())));
+    }
+
+    #[test]
+    fn test_flash_loan_limits() {
+        let (env, token, lender, market) = setup();
+        let borrower_id = register_borrower(&env, &market, 0, true);
+        token.approve(&market, &borrower_id, &1_000_000, &100);
+
+        let res = lender.try_set_terms(&1_001, &1_000);
+        assert_eq!(res, Err(Ok(FlashLenderError::InvalidFee.into())));
+        let res = lender.try_flash_loan(&borrower_id, &0, &Bytes::new(&env));
+        assert_eq!(res, Err(Ok(FlashLenderError::InvalidAmount.into())));
+
+        // off when the cap is 0
+        lender.set_terms(&0, &0);
+        let res = lender.try_flash_loan(&borrower_id, &1, &Bytes::new(&env));
+        assert_eq!(res, Err(Ok(FlashLenderError::LoanTooLarge.into())));
+
+        lender.set_terms(&0, &3_000);
+        assert_eq!(lender.max_flash_loan(), 3_000);
+        let res = lender.try_flash_loan(&borrower_id, &3_001, &Bytes::new(&env));
+        assert_eq!(res, Err(Ok(FlashLenderError::LoanTooLarge.into())));
+        lender.flash_loan(&borrower_id, &3_000, &Bytes::new(&env));
+
+        // loans count against the minter quota of the lender
+        lender.set_terms(&0, &1_000_000);
+        lender.flash_loan(&borrower_id, &97_000, &Bytes::new(&env));
+        assert!(lender
+            .try_flash_loan(&borrower_id, &1, &Bytes::new(&env))
+            .is_err());
+        assert_eq!(token.total_supply(), 1000);
+
+        // and stop once it is no longer a minter
+        token.remove_minter(&lender.address);
+        assert!(lender
+            .try_flash_loan(&borrower_id, &1, &Bytes::new(&env))
+            .is_err());
+    }
+}
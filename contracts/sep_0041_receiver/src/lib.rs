@@ -0,0 +1,93 @@
+#![no_std]
+use soroban_sdk::{
+    contract, contractclient, contracterror, contractimpl, contracttype, panic_with_error, Address,
+    Bytes, Env,
+};
+
+mod test;
+
+// memos longer than this make the hook fail
+const MAX_MEMO_LENGTH: u32 = 32;
+
+#[contracttype]
+#[derive(Debug)]
+pub enum DataKey {
+    Token,
+    MinDeposit,
+    Deposited(Address),
+    LastMemo,
+}
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ReceiverError {
+    MemoTooLong = 1,
+}
+
+// the receiver interface of the SEP-41 token. Declared here rather than taken from the token
+// crate, which would link the whole token contract into the receiver
+#[contractclient(name = "ReceiverHookClient")]
+pub trait ISep0041Receiver {
+    fn on_sep41_received(env: &Env, from: Address, amount: i128, data: Bytes) -> bool;
+}
+
+// an example treasury that books every deposit of `token` made with `transfer_and_call`,
+// deposits below the minimum are rejected
+#[contract]
+pub struct Sep0041Receiver;
+
+#[contractimpl]
+impl Sep0041Receiver {
+    pub fn __constructor(env: &Env, token: Address, min_deposit: i128) {
+        env.storage().instance().set(&DataKey::Token, &token);
+        env.storage()
+            .instance()
+            .set(&DataKey::MinDeposit, &min_deposit);
+    }
+
+    pub fn token(env: &Env) -> Address {
+        env.storage().instance().get(&DataKey::Token).unwrap()
+    }
+
+    pub fn deposited(env: &Env, from: Address) -> i128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::Deposited(from))
+            .unwrap_or(0)
+    }
+
+    pub fn last_memo(env: &Env) -> Bytes {
+        env.storage()
+            .instance()
+            .get(&DataKey::LastMemo)
+            .unwrap_or_else(|| Bytes::new(env))
+    }
+}
+
+#[contractimpl]
+impl ISep0041Receiver for Sep0041Receiver {
+    fn on_sep41_received(env: &Env, from: Address, amount: i128, data: Bytes) -> bool {
+        // only the token itself reports deposits, anyone else could book ones never made
+        Self::token(env).require_auth();
+
+        if data.len() > MAX_MEMO_LENGTH {
+            panic_with_error!(env, ReceiverError::MemoTooLong);
+        }
+
+        let min_deposit: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::MinDeposit)
+            .unwrap_or(0);
+        if amount < min_deposit {
+            return false;
+        }
+
+        let deposited: i128 = Self::deposited(env, from.clone());
+        env.storage()
+            .instance()
+            .set(&DataKey::Deposited(from), &(deposited + amount));
+        env.storage().instance().set(&DataKey::LastMemo, &data);
+        true
+    }
+}
@@ -0,0 +1,140 @@
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod test {
+    use crate::{Sep0041Receiver, Sep0041ReceiverClient};
+    use sep_0041::contract_sep_41::{Sep0041, Sep0041Client};
+    use sep_0041::Sep0041Error;
+    use soroban_sdk::{
+        symbol_short,
+        testutils::{Address as _, Events},
+        vec, Address, Bytes, Env, IntoVal, String,
+    };
+
+    fn setup() -> (
+        Env,
+        Sep0041Client<'static>,
+        Sep0041ReceiverClient<'static>,
+        Address,
+    ) {
+        let env: Env = Env::default();
+
+        let admin: Address = Address::generate(&env);
+        let token_id = env.register(
+            Sep0041,
+            (
+                admin,
+                18_u32,
+                String::from_str(&env, "loaded"),
+                String::from_str(&env, "lsd"),
+                false,
+                None::<i128>,
+                None::<Address>,
+            ),
+        );
+        let receiver_id = env.register(Sep0041Receiver, (token_id.clone(), 100_i128));
+        env.mock_all_auths();
+
+        let token = Sep0041Client::new(&env, &token_id);
+        let receiver = Sep0041ReceiverClient::new(&env, &receiver_id);
+        let sender: Address = Address::generate(&env);
+        token.mint(&sender, &1000);
+
+        (env, token, receiver, sender)
+    }
+
+    #[test]
+    fn test_transfer_and_call() {
+        let (env, token, receiver, sender) = setup();
+        let memo: Bytes = Bytes::from_slice(&env, b"march payroll");
+
+        token.transfer_and_call(&sender, &receiver.address, &300, &memo);
+
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    token.address.clone(),
+                    (
+                        symbol_short!("transfer"),
+                        sender.clone(),
+                        receiver.address.clone()
+                    )
+                        .into_val(&env),
+                    300_i128.into_val(&env),
+                ),
+            ]
+        );
+        assert_eq!(token.balance(&sender), 700);
+        assert_eq!(token.balance(&receiver.address), 300);
+        assert_eq!(receiver.deposited(&sender), 300);
+        assert_eq!(receiver.last_memo(), memo);
+    }
+
+    #[test]
+    fn test_transfer_and_call_reverts_when_rejected() {
+        let (env, token, receiver, sender) = setup();
+
+        // below the receiver minimum
+        let res = token.try_transfer_and_call(&sender, &receiver.address, &50, &Bytes::new(&env));
+        assert_eq!(res, Err(Ok(Sep0041Error::ReceiverRejected.into())));
+        assert_eq!(token.balance(&sender), 1000);
+        assert_eq!(token.balance(&receiver.address), 0);
+        assert_eq!(receiver.deposited(&sender), 0);
+    }
+
+    #[test]
+    fn test_transfer_and_call_reverts_when_hook_fails() {
+        let (env, token, receiver, sender) = setup();
+
+        let memo: Bytes = Bytes::from_array(&env, &[1; 33]);
+        let res = token.try_transfer_and_call(&sender, &receiver.address, &300, &memo);
+        assert_eq!(res, Err(Ok(Sep0041Error::ReceiverRejected.into())));
+        assert_eq!(token.balance(&sender), 1000);
+        assert_eq!(token.balance(&receiver.address), 0);
+    }
+
+    #[test]
+    fn test_transfer_and_call_to_non_receiver_reverts() {
+        let (env, token, _, sender) = setup();
+        let not_a_receiver: Address = Address::generate(&env);
+
+        let res = token.try_transfer_and_call(&sender, &not_a_receiver, &300, &Bytes::new(&env));
+        assert_eq!(res, Err(Ok(Sep0041Error::ReceiverRejected.into())));
+        assert_eq!(token.balance(&sender), 1000);
+        assert_eq!(token.balance(&not_a_receiver), 0);
+    }
+
+    #[test]
+    fn test_hook_only_accepts_its_token() {
+        let (env, token, receiver, sender) = setup();
+
+        // a deposit reported by anyone but the token is refused
+        env.mock_auths(&[]);
+        let res = receiver.try_on_sep41_received(&sender, &300, &Bytes::new(&env));
+        assert!(res.is_err());
+        assert_eq!(receiver.deposited(&sender), 0);
+
+        // and so is one made with another token
+        env.mock_all_auths();
+        let other_token_id = env.register(
+            Sep0041,
+            (
+                token.admin(),
+                18_u32,
+                String::from_str(&env, "other"),
+                String::from_str(&env, "oth"),
+                false,
+                None::<i128>,
+                None::<Address>,
+            ),
+        );
+        let other_token = Sep0041Client::new(&env, &other_token_id);
+        other_token.mint(&sender, &1000);
+        let res =
+            other_token.try_transfer_and_call(&sender, &receiver.address, &300, &Bytes::new(&env));
+        assert_eq!(res, Err(Ok(Sep0041Error::ReceiverRejected.into())));
+        assert_eq!(other_token.balance(&sender), 1000);
+        assert_eq!(receiver.deposited(&sender), 0);
+    }
+}